@@ -1,21 +1,9 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 4 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_part_1 ... bench:     118,604 ns/iter (+/- 11,914)
-    test bench::bench_part_2 ... bench:     100,205 ns/iter (+/- 26,558)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
 
+use aoc_runner::Day;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -24,12 +12,13 @@ macro_rules! err {
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    day().run()
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&input)?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&input)?)?;
-    Ok(())
+fn day() -> Day<String> {
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
 }
 
 fn part_1(input: &str) -> Result<isize> {
@@ -59,54 +48,17 @@ fn part_2(input: &str) -> Result<usize> {
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_part_1() -> Result<()> {
-        assert_eq!(part_1("(())")?, 0);
-        assert_eq!(part_1("()()")?, 0);
-        assert_eq!(part_1("(((")?, 3);
-        assert_eq!(part_1("(()(()(")?, 3);
-        assert_eq!(part_1("))(((((")?, 3);
-        assert_eq!(part_1("())")?, -1);
-        assert_eq!(part_1("))(")?, -1);
-        assert_eq!(part_1(")))")?, -3);
-        assert_eq!(part_1(")())())")?, -3);
-        Ok(())
-    }
-
-    #[test]
-    fn test_part_2() -> Result<()> {
-        assert_eq!(part_2(")")?, 1);
-        assert_eq!(part_2("()())")?, 5);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        b.iter(|| test::black_box(part_1(&read_input_file()?)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        b.iter(|| test::black_box(part_2(&read_input_file()?)));
-        Ok(())
-    }
-}
+aoc_runner::examples!(
+    day(),
+    example_1,
+    example_2,
+    example_3,
+    example_4,
+    example_5,
+    example_6,
+    example_7,
+    example_8,
+    example_9,
+    example_10,
+    example_11
+);
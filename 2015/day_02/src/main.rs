@@ -1,26 +1,19 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 4 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_part_1 ... bench:       4,563 ns/iter (+/- 831)
-    test bench::bench_part_2 ... bench:       3,687 ns/iter (+/- 89)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Print a report for every box : cargo run --release -- --report [--unit in|cm] [--slack smallest-side|none|<percent>%] [--bow volume|none|<length>] < input/input.txt
+// Export the report as CSV : cargo run --release -- --csv <file> [--unit ...] [--slack ...] [--bow ...] < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
-use std::convert::TryFrom;
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::str::FromStr;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -28,125 +21,523 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Centimeter,
+    Inch,
+}
+
+impl Unit {
+    fn in_centimeters(&self) -> f64 {
+        match self {
+            Unit::Centimeter => 1.0,
+            Unit::Inch => 2.54,
+        }
+    }
+
+    fn convert(&self, value: f64, to: Unit) -> f64 {
+        if *self == to {
+            value
+        } else {
+            value * self.in_centimeters() / to.in_centimeters()
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Box<dyn Error>;
+
+    fn from_str(unit: &str) -> Result<Self> {
+        match unit {
+            "cm" => Ok(Unit::Centimeter),
+            "in" => Ok(Unit::Inch),
+            _ => err!("Unknown unit : {}", unit),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Centimeter => write!(f, "centimeters"),
+            Unit::Inch => write!(f, "inches"),
+        }
+    }
+}
+
+// Dimensions sorted in increasing order, so that a box doesn't depend on the order they were
+// given in, all of them in the same unit
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Dimensions {
-    length: usize,
-    width: usize,
-    height: usize,
+    sides: [f64; 3],
 }
 
 impl Dimensions {
-    fn calculate_surface_area(&self) -> usize {
-        2 * self.length * self.width
-            + 2 * self.width * self.height
-            + 2 * self.height * self.length
-            + std::cmp::min(
-                self.length * self.width,
-                std::cmp::min(self.width * self.height, self.height * self.length),
-            )
+    fn new(mut sides: [f64; 3]) -> Self {
+        sides.sort_unstable_by(|a, b| a.total_cmp(b));
+        Dimensions { sides }
     }
 
-    fn calculate_ribbon_length(&self) -> usize {
-        self.length * 2 + self.width * 2 + self.height * 2
-            - std::cmp::max(self.length, std::cmp::max(self.width, self.height)) * 2
-            + self.length * self.width * self.height
+    fn surface_area(&self) -> f64 {
+        let [a, b, c] = self.sides;
+        2.0 * (a * b + b * c + c * a)
+    }
+
+    fn smallest_side_area(&self) -> f64 {
+        self.sides[0] * self.sides[1]
+    }
+
+    fn smallest_perimeter(&self) -> f64 {
+        2.0 * (self.sides[0] + self.sides[1])
     }
-}
 
-impl TryFrom<&str> for Dimensions {
-    type Error = Box<dyn Error>;
+    fn volume(&self) -> f64 {
+        self.sides.iter().product()
+    }
 
-    fn try_from(value: &str) -> Result<Self> {
+    // Every dimension is a decimal number with an optional unit, a dimension without unit being
+    // in the report unit : "2x3x4", "2.5cm x 1in x 4"
+    fn parse(value: &str, unit: Unit) -> Result<Self> {
         use regex::Regex;
 
         lazy_static! {
-            static ref DAY_02_DIMENSIONS_REGEX: Regex =
-                Regex::new(r"^(?P<length>\d+)x(?P<width>\d+)x(?P<height>\d+)$")
-                    .expect("Invalid DAY_02_DIMENSIONS_REGEX!");
+            static ref DAY_02_SIDE_REGEX: Regex =
+                Regex::new(r"^\s*(?P<value>\d+(?:\.\d+)?)\s*(?P<unit>[a-z]+)?\s*$")
+                    .expect("Invalid DAY_02_SIDE_REGEX!");
         }
 
-        if let Some(cap) = DAY_02_DIMENSIONS_REGEX.captures(value) {
-            Ok(Self {
-                length: cap["length"].parse::<usize>()?,
-                width: cap["width"].parse::<usize>()?,
-                height: cap["height"].parse::<usize>()?,
-            })
-        } else {
+        let mut sides = [0.0; 3];
+        let mut count = 0;
+        for side in value.split('x') {
+            let cap = match DAY_02_SIDE_REGEX.captures(side) {
+                Some(cap) if count < 3 => cap,
+                _ => err!("Couldn't parse input: {}", value),
+            };
+            let side_unit = match cap.name("unit") {
+                Some(side_unit) => Unit::from_str(side_unit.as_str())?,
+                None => unit,
+            };
+            sides[count] = side_unit.convert(cap["value"].parse::<f64>()?, unit);
+            count += 1;
+        }
+        if count != 3 {
             err!("Couldn't parse input: {}", value)
         }
+
+        Ok(Dimensions::new(sides))
+    }
+}
+
+// Extra paper on top of the surface area of the box
+trait SlackPolicy: fmt::Display {
+    fn slack(&self, dimensions: &Dimensions) -> f64;
+}
+
+// Area of the smallest side, as the elves do
+struct SmallestSide;
+
+impl SlackPolicy for SmallestSide {
+    fn slack(&self, dimensions: &Dimensions) -> f64 {
+        dimensions.smallest_side_area()
+    }
+}
+
+impl fmt::Display for SmallestSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "smallest side")
+    }
+}
+
+struct NoSlack;
+
+impl SlackPolicy for NoSlack {
+    fn slack(&self, _: &Dimensions) -> f64 {
+        0.0
+    }
+}
+
+impl fmt::Display for NoSlack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no slack")
+    }
+}
+
+// Percentage of the surface area
+struct SurfacePercentage(f64);
+
+impl SlackPolicy for SurfacePercentage {
+    fn slack(&self, dimensions: &Dimensions) -> f64 {
+        dimensions.surface_area() * self.0 / 100.0
+    }
+}
+
+impl fmt::Display for SurfacePercentage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}% of the surface", self.0)
+    }
+}
+
+fn slack_policy(name: &str) -> Result<Box<dyn SlackPolicy>> {
+    match name {
+        "smallest-side" => Ok(Box::new(SmallestSide)),
+        "none" => Ok(Box::new(NoSlack)),
+        _ => match name.strip_suffix('%').map(str::parse::<f64>) {
+            Some(Ok(percentage)) if percentage.is_finite() && percentage >= 0.0 => {
+                Ok(Box::new(SurfacePercentage(percentage)))
+            }
+            _ => err!("Unknown slack policy : {}", name),
+        },
+    }
+}
+
+// Ribbon tied as a bow on top of the ribbon wrapped around the smallest perimeter
+trait BowPolicy: fmt::Display {
+    fn bow(&self, dimensions: &Dimensions) -> f64;
+}
+
+// As long as the volume of the box is, as the elves do
+struct Volume;
+
+impl BowPolicy for Volume {
+    fn bow(&self, dimensions: &Dimensions) -> f64 {
+        dimensions.volume()
+    }
+}
+
+impl fmt::Display for Volume {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "volume")
+    }
+}
+
+struct NoBow;
+
+impl BowPolicy for NoBow {
+    fn bow(&self, _: &Dimensions) -> f64 {
+        0.0
+    }
+}
+
+impl fmt::Display for NoBow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no bow")
+    }
+}
+
+// Same length whatever the box
+struct FixedBow(f64);
+
+impl BowPolicy for FixedBow {
+    fn bow(&self, _: &Dimensions) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for FixedBow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "fixed bow of {}", self.0)
+    }
+}
+
+fn bow_policy(name: &str) -> Result<Box<dyn BowPolicy>> {
+    match name {
+        "volume" => Ok(Box::new(Volume)),
+        "none" => Ok(Box::new(NoBow)),
+        _ => match name.parse::<f64>() {
+            Ok(length) if length.is_finite() && length >= 0.0 => Ok(Box::new(FixedBow(length))),
+            _ => err!("Unknown bow policy : {}", name),
+        },
+    }
+}
+
+struct Costing {
+    slack: Box<dyn SlackPolicy>,
+    bow: Box<dyn BowPolicy>,
+}
+
+impl Costing {
+    fn paper(&self, dimensions: &Dimensions) -> f64 {
+        dimensions.surface_area() + self.slack.slack(dimensions)
+    }
+
+    fn ribbon(&self, dimensions: &Dimensions) -> f64 {
+        dimensions.smallest_perimeter() + self.bow.bow(dimensions)
+    }
+}
+
+impl Default for Costing {
+    fn default() -> Self {
+        Costing {
+            slack: Box::new(SmallestSide),
+            bow: Box::new(Volume),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    line: usize,
+    dimensions: Dimensions,
+    paper: f64,
+    ribbon: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Report {
+    rows: Vec<Row>,
+}
+
+impl Report {
+    fn new(boxes: &[Dimensions], costing: &Costing) -> Self {
+        Report {
+            rows: boxes
+                .iter()
+                .enumerate()
+                .map(|(i, dimensions)| Row {
+                    line: i + 1,
+                    dimensions: *dimensions,
+                    paper: costing.paper(dimensions),
+                    ribbon: costing.ribbon(dimensions),
+                })
+                .collect(),
+        }
+    }
+
+    // Summing from 0 rather than with `sum`, which starts from -0 for floats
+    fn total_paper(&self) -> f64 {
+        self.rows.iter().fold(0.0, |total, row| total + row.paper)
+    }
+
+    fn total_ribbon(&self) -> f64 {
+        self.rows.iter().fold(0.0, |total, row| total + row.ribbon)
+    }
+
+    // By volume, the first one of the input on ties
+    fn largest(&self) -> Option<&Row> {
+        self.rows
+            .iter()
+            .fold(None, |largest: Option<&Row>, row| match largest {
+                Some(largest) if largest.dimensions.volume() >= row.dimensions.volume() => {
+                    Some(largest)
+                }
+                _ => Some(row),
+            })
+    }
+
+    // One row per box, the largest one being flagged, followed by the totals
+    fn to_csv(&self) -> String {
+        let largest = self.largest().map(|row| row.line);
+        let mut csv = String::from("line,side_1,side_2,side_3,volume,paper,ribbon,largest\n");
+        for row in &self.rows {
+            let [a, b, c] = row.dimensions.sides;
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                row.line,
+                a,
+                b,
+                c,
+                row.dimensions.volume(),
+                row.paper,
+                row.ribbon,
+                largest == Some(row.line)
+            ));
+        }
+        csv.push_str(&format!(
+            "total,,,,,{},{},\n",
+            self.total_paper(),
+            self.total_ribbon()
+        ));
+        csv
     }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[
+        Flag::Switch("--report"),
+        Flag::Value("--csv"),
+        Flag::Value("--unit"),
+        Flag::Value("--slack"),
+        Flag::Value("--bow"),
+    ])?;
+    let csv = options.value("--csv");
+    if csv.is_some() || options.has("--report") {
+        let unit = options.parse_value::<Unit>("--unit")?.unwrap_or(Unit::Inch);
+        let mut costing = Costing::default();
+        if let Some(slack) = options.value("--slack") {
+            costing.slack = slack_policy(slack)?;
+        }
+        if let Some(bow) = options.value("--bow") {
+            costing.bow = bow_policy(bow)?;
+        }
+        return report(&options, unit, &costing, csv);
+    }
 
-    let dimensions: Vec<Dimensions> = parse_input(&input)?;
+    day().run_with(&options)
+}
+
+fn day() -> Day<Vec<Dimensions>> {
+    Day::new(|input| parse_input(input, Unit::Inch))
+        .part(|dimensions| part_1(dimensions))
+        .part(|dimensions| part_2(dimensions))
+}
+
+fn parse_input(input: &str, unit: Unit) -> Result<Vec<Dimensions>> {
+    input
+        .lines()
+        .map(|line| Dimensions::parse(line, unit))
+        .collect()
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&dimensions))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&dimensions))?;
+fn report(options: &Options, unit: Unit, costing: &Costing, csv: Option<&str>) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let report = Report::new(&parse_input(&input, unit)?, costing);
+
+    if let Some(file) = csv {
+        fs::write(file, report.to_csv())?;
+        writeln!(io::stdout(), "Report saved to \"{}\"", file)?;
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    writeln!(
+        stdout,
+        "Slack : {}, bow : {}, lengths in {}",
+        costing.slack, costing.bow, unit
+    )?;
+    for row in &report.rows {
+        let [a, b, c] = row.dimensions.sides;
+        writeln!(
+            stdout,
+            "Box {} ({}x{}x{}) : paper {:.2}, ribbon {:.2}",
+            row.line, a, b, c, row.paper, row.ribbon
+        )?;
+    }
+    if let Some(largest) = report.largest() {
+        writeln!(
+            stdout,
+            "Largest box : box {} ({:.2})",
+            largest.line,
+            largest.dimensions.volume()
+        )?;
+    }
+    writeln!(
+        stdout,
+        "Total : paper {:.2}, ribbon {:.2}",
+        report.total_paper(),
+        report.total_ribbon()
+    )?;
     Ok(())
 }
 
-fn parse_input(input: &str) -> Result<Vec<Dimensions>> {
-    input.lines().map(Dimensions::try_from).collect()
+// The elves only give whole dimensions in inches, summed exactly as integers. Sides are sorted, the
+// first two ones making the smallest side
+fn whole_sides(boxes: &[Dimensions]) -> Result<Vec<[u64; 3]>> {
+    let mut whole_sides = vec![];
+    for dimensions in boxes {
+        let [a, b, c] = dimensions.sides;
+        if dimensions
+            .sides
+            .iter()
+            .any(|side| side.fract() != 0.0 || *side > u32::MAX as f64)
+        {
+            err!("Dimensions are not whole inches : {}x{}x{}", a, b, c)
+        }
+        whole_sides.push([a as u64, b as u64, c as u64]);
+    }
+    Ok(whole_sides)
 }
 
-fn part_1(dimensions: &[Dimensions]) -> usize {
-    dimensions.iter().map(|v| v.calculate_surface_area()).sum()
+fn part_1(boxes: &[Dimensions]) -> Result<u64> {
+    Ok(whole_sides(boxes)?
+        .iter()
+        .map(|[a, b, c]| 2 * (a * b + b * c + c * a) + a * b)
+        .sum())
 }
 
-fn part_2(dimensions: &[Dimensions]) -> usize {
-    dimensions.iter().map(|v| v.calculate_ribbon_length()).sum()
+fn part_2(boxes: &[Dimensions]) -> Result<u64> {
+    Ok(whole_sides(boxes)?
+        .iter()
+        .map(|[a, b, c]| 2 * (a + b) + a * b * c)
+        .sum())
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_input() -> String {
-        "2x3x4\n1x1x10".to_string()
-    }
-
     #[test]
-    fn test_part_1() -> Result<()> {
-        let dimensions = parse_input(&get_input())?;
-        assert_eq!(part_1(&dimensions), 58 + 43);
+    fn test_dimensions() -> Result<()> {
+        let dimensions = Dimensions::parse("4x2x3", Unit::Inch)?;
+        assert_eq!(dimensions, Dimensions::parse("2x3x4", Unit::Inch)?);
+        assert_eq!(dimensions.sides, [2.0, 3.0, 4.0]);
+
+        let dimensions = Dimensions::parse("2.54cm x 1.5 x 2in", Unit::Inch)?;
+        assert_eq!(dimensions.sides, [1.0, 1.5, 2.0]);
+        let dimensions = Dimensions::parse("1inx1x1", Unit::Centimeter)?;
+        assert_eq!(dimensions.sides, [1.0, 1.0, 2.54]);
+
+        assert!(Dimensions::parse("1x2", Unit::Inch).is_err());
+        assert!(Dimensions::parse("1x2x3x4", Unit::Inch).is_err());
+        assert!(Dimensions::parse("1x2x3ft", Unit::Inch).is_err());
+        assert!(Dimensions::parse("1x-2x3", Unit::Inch).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let dimensions = parse_input(&get_input())?;
-        assert_eq!(part_2(&dimensions), 34 + 14);
+    fn test_policies() -> Result<()> {
+        let dimensions = Dimensions::parse("2x3x4", Unit::Inch)?;
+        let costing = Costing {
+            slack: slack_policy("50%")?,
+            bow: bow_policy("1.5")?,
+        };
+        assert_eq!(costing.paper(&dimensions), 78.0);
+        assert_eq!(costing.ribbon(&dimensions), 11.5);
+        assert_eq!(costing.slack.to_string(), "50% of the surface");
+
+        let costing = Costing {
+            slack: slack_policy("none")?,
+            bow: bow_policy("none")?,
+        };
+        assert_eq!(costing.paper(&dimensions), 52.0);
+        assert_eq!(costing.ribbon(&dimensions), 10.0);
+        assert!(slack_policy("-5%").is_err());
+        assert!(bow_policy("big").is_err());
+        assert!(bow_policy("inf").is_err());
         Ok(())
     }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+    #[test]
+    fn test_report() -> Result<()> {
+        let boxes = parse_input("2x3x4\n1x1x10\n4x3x2", Unit::Inch)?;
+        let report = Report::new(&boxes, &Costing::default());
+        assert_eq!(report.total_paper(), 159.0);
+        assert_eq!(report.total_ribbon(), 82.0);
+        assert_eq!(report.largest().map(|row| row.line), Some(1));
+        assert_eq!(
+            report.to_csv(),
+            "line,side_1,side_2,side_3,volume,paper,ribbon,largest\n\
+             1,2,3,4,24,58,34,true\n\
+             2,1,1,10,10,43,14,false\n\
+             3,2,3,4,24,58,34,false\n\
+             total,,,,,159,82,\n"
+        );
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let dimensions = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&dimensions)));
+        let report = Report::new(&[], &Costing::default());
+        assert_eq!(format!("{:.2}", report.total_paper()), "0.00");
+        assert_eq!(report.largest(), None);
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let dimensions = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&dimensions)));
+    #[test]
+    fn test_whole_parts() -> Result<()> {
+        let boxes = parse_input("2x3x4\n1x1x10\n2.54cmx1x1", Unit::Inch)?;
+        assert_eq!(part_1(&boxes)?, 58 + 43 + 7);
+        assert_eq!(part_2(&boxes)?, 34 + 14 + 5);
+        assert_eq!(part_1(&[])?, 0);
+        assert!(part_1(&parse_input("1.5x1x1", Unit::Inch)?).is_err());
         Ok(())
     }
 }
@@ -1,22 +1,15 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 4 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_part_1 ... bench:     683,135 ns/iter (+/- 46,081)
-    test bench::bench_part_2 ... bench:     699,459 ns/iter (+/- 59,966)
-
-*/
-
-use std::collections::HashSet;
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the turn order, agents being numbered from 0 : cargo run --release -- --order 0,1 --visits < input/input.txt
+// Share moves between agents in turn : cargo run --release -- --agents 3 --visits < input/input.txt
+// Render visits as a heatmap : cargo run --release -- --agents 2 --heatmap <file> < input/input.txt
+
+use aoc_runner::{Day, Flag, Options};
+use std::collections::HashMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fs::File;
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -24,130 +17,228 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+// (x, y), x going east and y going north
+type House = (i64, i64);
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&input)?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&input)?)?;
-    Ok(())
+// Heatmaps bigger than this are refused rather than allocated, one byte per pixel
+const HEATMAP_MAX_PIXELS: u64 = 1 << 26;
+
+// Agent making each move, cycling through the order : [0, 1] alternates between two agents,
+// [0, 0, 1] lets the first one make two moves before the second one makes one
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TurnOrder {
+    order: Vec<usize>,
+    agents: usize,
 }
 
-fn part_1(input: &str) -> Result<usize> {
-    let mut x = 0;
-    let mut y = 0;
-    let mut positions = vec![(x, y)];
-
-    for c in input.chars() {
-        match c {
-            '>' => x += 1,
-            '<' => x -= 1,
-            '^' => y += 1,
-            'v' => y -= 1,
-            _ => err!("Invalid character found: {}", c),
+impl TurnOrder {
+    fn new(order: Vec<usize>) -> Result<Self> {
+        let agents = match order.iter().max() {
+            Some(&max) => max + 1,
+            None => err!("The turn order needs at least one agent"),
+        };
+        if let Some(agent) = (0..agents).find(|agent| !order.contains(agent)) {
+            err!("Agent {} never moves in turn order {:?}", agent, order)
         }
-        positions.push((x, y));
+        Ok(TurnOrder { order, agents })
     }
 
-    let unique_positions: HashSet<(i32, i32)> = positions.iter().cloned().collect();
-    Ok(unique_positions.len())
+    fn round_robin(agents: usize) -> Result<Self> {
+        TurnOrder::new((0..agents).collect())
+    }
 }
 
-fn part_2(input: &str) -> Result<usize> {
-    let mut santa_x = 0;
-    let mut santa_y = 0;
-    let mut robot_x = 0;
-    let mut robot_y = 0;
-
-    let mut positions = vec![(santa_x, santa_y)];
-
-    for (i, c) in input.chars().enumerate() {
-        match c {
-            '>' => {
-                if i % 2 == 0 {
-                    santa_x += 1;
-                } else {
-                    robot_x += 1;
-                }
-            }
-            '<' => {
-                if i % 2 == 0 {
-                    santa_x -= 1;
-                } else {
-                    robot_x -= 1;
-                }
-            }
-            '^' => {
-                if i % 2 == 0 {
-                    santa_y += 1;
-                } else {
-                    robot_y += 1;
-                }
-            }
-            'v' => {
-                if i % 2 == 0 {
-                    santa_y -= 1;
-                } else {
-                    robot_y -= 1;
-                }
-            }
-            _ => err!("Invalid character found: {}", c),
+#[derive(Debug, Default)]
+struct Visits {
+    // Presents delivered to every house, each agent delivering one at the start
+    counts: HashMap<House, usize>,
+}
+
+impl Visits {
+    fn simulate(moves: &str, turn_order: &TurnOrder) -> Result<Self> {
+        let mut agents = vec![(0, 0); turn_order.agents];
+        let mut visits = Visits::default();
+        for &house in &agents {
+            visits.deliver(house);
         }
-        if i % 2 == 0 {
-            positions.push((santa_x, santa_y));
-        } else {
-            positions.push((robot_x, robot_y));
+
+        for (c, &agent) in moves
+            .trim_end()
+            .chars()
+            .zip(turn_order.order.iter().cycle())
+        {
+            let (x, y) = &mut agents[agent];
+            match c {
+                '>' => *x += 1,
+                '<' => *x -= 1,
+                '^' => *y += 1,
+                'v' => *y -= 1,
+                _ => err!("Invalid character found: {}", c),
+            }
+            visits.deliver(agents[agent]);
         }
+        Ok(visits)
     }
 
-    let unique_positions: HashSet<(i32, i32)> = positions.iter().cloned().collect();
-    Ok(unique_positions.len())
-}
+    fn deliver(&mut self, house: House) {
+        *self.counts.entry(house).or_insert(0) += 1;
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn houses(&self) -> usize {
+        self.counts.len()
+    }
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        assert_eq!(part_1(">")?, 2);
-        assert_eq!(part_1("^>v<")?, 4);
-        assert_eq!(part_1("^v^v^v^v^v")?, 2);
-        Ok(())
+    // Ties are broken by the southernmost, then westernmost house
+    fn most_visited(&self) -> Option<(House, usize)> {
+        self.counts
+            .iter()
+            .max_by_key(|&(&(x, y), &count)| (count, -y, -x))
+            .map(|(&house, &count)| (house, count))
     }
 
-    #[test]
-    fn test_part_2() -> Result<()> {
-        assert_eq!(part_2("^v")?, 3);
-        assert_eq!(part_2("^>v<")?, 3);
-        assert_eq!(part_2("^v^v^v^v^v")?, 11);
+    // One pixel per house of the bounding box, north at the top. Brightness grows with the
+    // logarithm of the visits so that a few very visited houses don't hide the others
+    fn heatmap(&self, output: impl Write) -> Result<()> {
+        use image::png::PNGEncoder;
+        use image::ColorType;
+
+        let houses = self.counts.keys();
+        let (min_x, max_x, min_y, max_y) =
+            houses.fold((0, 0, 0, 0), |(min_x, max_x, min_y, max_y), &(x, y)| {
+                (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+            });
+        let (width, height) = match (
+            max_x.abs_diff(min_x).checked_add(1),
+            max_y.abs_diff(min_y).checked_add(1),
+        ) {
+            (Some(width), Some(height)) if width.saturating_mul(height) <= HEATMAP_MAX_PIXELS => {
+                (width as usize, height as usize)
+            }
+            _ => err!(
+                "Visited houses span more than the limit of {} pixels",
+                HEATMAP_MAX_PIXELS
+            ),
+        };
+        let max_count = self.counts.values().copied().max().unwrap_or(1);
+
+        let mut pixels = vec![0u8; width * height];
+        for (&(x, y), &count) in &self.counts {
+            let brightness = (1.0 + count as f64).ln() / (1.0 + max_count as f64).ln();
+            pixels[(max_y - y) as usize * width + (x - min_x) as usize] =
+                (brightness * 255.0).round() as u8;
+        }
+        PNGEncoder::new(output).encode(&pixels, width as u32, height as u32, ColorType::Gray(8))?;
         Ok(())
     }
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+fn main() -> Result<()> {
+    let options = Options::from_env(&[
+        Flag::Value("--order"),
+        Flag::Value("--agents"),
+        Flag::Switch("--visits"),
+        Flag::Value("--heatmap"),
+    ])?;
+    let turn_order = match (options.value("--order"), options.value("--agents")) {
+        (Some(_), Some(_)) => err!("--order and --agents can't be used together"),
+        (Some(order), None) => TurnOrder::new(
+            order
+                .split(',')
+                .map(|agent| agent.parse::<usize>())
+                .collect::<::std::result::Result<Vec<usize>, _>>()?,
+        )?,
+        (None, Some(agents)) => TurnOrder::round_robin(agents.parse::<usize>()?)?,
+        (None, None) => TurnOrder::round_robin(1)?,
+    };
+    let heatmap = options.value("--heatmap");
+    if heatmap.is_some() || options.has("--visits") {
+        return report(&options, &turn_order, heatmap);
+    }
+
+    day().run_with(&options)
+}
+
+fn day() -> Day<String> {
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+}
 
+fn report(options: &Options, turn_order: &TurnOrder, heatmap: Option<&str>) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let visits = Visits::simulate(&input, turn_order)?;
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "Houses visited : {}", visits.houses())?;
+    if let Some(((x, y), count)) = visits.most_visited() {
+        writeln!(
+            stdout,
+            "Most visited house : ({}, {}), {} presents",
+            x, y, count
+        )?;
+    }
+    if let Some(file) = heatmap {
+        visits.heatmap(File::create(file)?)?;
+        writeln!(stdout, "Heatmap saved to \"{}\"", file)?;
+    }
+    Ok(())
+}
+
+fn part_1(input: &str) -> Result<usize> {
+    Ok(Visits::simulate(input, &TurnOrder::round_robin(1)?)?.houses())
+}
+
+// Santa and Robo-Santa take turns
+fn part_2(input: &str) -> Result<usize> {
+    Ok(Visits::simulate(input, &TurnOrder::round_robin(2)?)?.houses())
+}
+
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2, example_3, example_4);
+
+#[cfg(test)]
+mod tests {
     use super::*;
-    use std::fs::File;
-    use test::Bencher;
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+    #[test]
+    fn test_turn_order() -> Result<()> {
+        // The first agent goes north twice then west, the second one only goes east
+        let visits = Visits::simulate("^^><", &TurnOrder::new(vec![0, 0, 1])?)?;
+        assert_eq!(visits.houses(), 5);
+        assert_eq!(visits.counts[&(0, 0)], 2);
+        assert_eq!(visits.counts[&(-1, 2)], 1);
+        assert_eq!(visits.most_visited(), Some(((0, 0), 2)));
+
+        assert!(TurnOrder::new(vec![]).is_err());
+        assert!(TurnOrder::new(vec![0, 2]).is_err());
+        assert!(Visits::simulate("^x", &TurnOrder::round_robin(1)?).is_err());
+        Ok(())
     }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        b.iter(|| test::black_box(part_1(&read_input_file()?)));
+    #[test]
+    fn test_most_visited() -> Result<()> {
+        let visits = Visits::simulate("^v^v>", &TurnOrder::round_robin(1)?)?;
+        assert_eq!(visits.most_visited(), Some(((0, 0), 3)));
+        // Both houses are visited twice, the southernmost one wins
+        let visits = Visits::simulate("^v^", &TurnOrder::round_robin(1)?)?;
+        assert_eq!(visits.most_visited(), Some(((0, 0), 2)));
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        b.iter(|| test::black_box(part_2(&read_input_file()?)));
+    #[test]
+    fn test_heatmap_limit() -> Result<()> {
+        let mut visits = Visits::simulate("^>>v", &TurnOrder::round_robin(1)?)?;
+        let mut png = vec![];
+        visits.heatmap(&mut png)?;
+        assert!(png.starts_with(b"\x89PNG"));
+
+        // A single column, but too tall
+        visits.deliver((0, 1 << 26));
+        assert!(visits.heatmap(vec![]).is_err());
+        // Spans which don't even fit in an i64
+        visits.deliver((i64::MIN, 0));
+        visits.deliver((i64::MAX, 0));
+        assert!(visits.heatmap(vec![]).is_err());
         Ok(())
     }
 }
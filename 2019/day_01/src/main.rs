@@ -1,18 +1,15 @@
-use std::io::{self, Read, Write};
+use aoc_runner::Day;
 
-type Result<T> = ::std::result::Result<T, Box<dyn (::std::error::Error)>>;
+type Result<T> = ::std::result::Result<T, Box<dyn ::std::error::Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
-
+fn part_1(input: &str) -> Result<i64> {
     let mut total_fuel: i64 = 0;
 
     for line in input.lines() {
@@ -20,11 +17,10 @@ fn part_1(input: &str) -> Result<()> {
         // If dividing two integers, floor() function seems to be automatically applied
         total_fuel += (mass / 3) - 2;
     }
-    writeln!(io::stdout(), "Part 1 : {}", total_fuel)?;
-    Ok(())
+    Ok(total_fuel)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<i64> {
     let mut total_fuel: i64 = 0;
 
     for line in input.lines() {
@@ -36,6 +32,5 @@ fn part_2(input: &str) -> Result<()> {
             current_fuel = (current_fuel / 3) - 2;
         }
     }
-    writeln!(io::stdout(), "Part 2 : {}", total_fuel)?;
-    Ok(())
-}
\ No newline at end of file
+    Ok(total_fuel)
+}
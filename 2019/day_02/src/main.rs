@@ -1,18 +1,16 @@
+use aoc_runner::Day;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<usize> {
     let mut steps: Vec<usize> = vec![];
 
     for step in input.trim().split(',') {
@@ -25,12 +23,10 @@ fn part_1(input: &str) -> Result<()> {
 
     let result = execute_intcode(steps)?;
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
-
-    Ok(())
+    Ok(result)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<usize> {
     let mut steps: Vec<usize> = vec![];
 
     for step in input.trim().split(',') {
@@ -46,14 +42,15 @@ fn part_2(input: &str) -> Result<()> {
 
             if let Ok(result) = execute_intcode(steps.clone()) {
                 if result == expected_result {
-                    writeln!(io::stdout(), "Part 2 : {}", 100 * noun + verb)?;
-                    return Ok(());
+                    return Ok(100 * noun + verb);
                 }
             }
         }
     }
 
-    err("IntCode could not find expected value!")
+    Err(Box::<dyn Error>::from(
+        "IntCode could not find expected value!",
+    ))
 }
 
 fn execute_intcode(mut steps: Vec<usize>) -> Result<usize> {
@@ -2,159 +2,399 @@
 extern crate lazy_static;
 extern crate regex;
 
+use aoc_runner::{Day, Flag, Options};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
-type WiresStepCount = (Vec<usize>, usize);
+type Point = (i64, i64);
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
+}
 
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+    Horizontal,
+    Vertical,
 }
 
-fn part_1(input: &str) -> Result<()> {
-    let coordinates = parse_input(input)?;
+// Axis-aligned piece of wire, `steps` being the number of steps taken by the wire to reach `start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    wire: usize,
+    start: Point,
+    end: Point,
+    steps: usize,
+}
 
-    let mut min_distance = ::std::u64::MAX;
-    use std::cmp;
+impl Segment {
+    fn orientation(&self) -> Orientation {
+        if self.start.1 == self.end.1 {
+            Orientation::Horizontal
+        } else {
+            Orientation::Vertical
+        }
+    }
 
-    for (coord, wires) in &coordinates {
-        if wires.0.len() > 1 {
-            min_distance = cmp::min(min_distance, (coord.0.abs() + coord.1.abs()) as u64)
+    // Coordinate of the line the segment lies on, along with its bounds on that line
+    fn line(&self) -> (i64, i64, i64) {
+        match self.orientation() {
+            Orientation::Horizontal => (
+                self.start.1,
+                self.start.0.min(self.end.0),
+                self.start.0.max(self.end.0),
+            ),
+            Orientation::Vertical => (
+                self.start.0,
+                self.start.1.min(self.end.1),
+                self.start.1.max(self.end.1),
+            ),
         }
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", min_distance)?;
+    fn steps_to(&self, point: Point) -> usize {
+        self.steps + manhattan_distance(self.start, point) as usize
+    }
+}
 
-    Ok(())
+fn manhattan_distance(a: Point, b: Point) -> u64 {
+    ((a.0 - b.0).abs() + (a.1 - b.1).abs()) as u64
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Crossing {
+    point: Point,
+    // Indexes of the two wires crossing, the smallest first
+    wires: (usize, usize),
+    // Fewest steps each wire takes to reach the crossing
+    steps: (usize, usize),
+}
+
+impl Crossing {
+    fn distance(&self) -> u64 {
+        manhattan_distance((0, 0), self.point)
+    }
+
+    fn combined_steps(&self) -> usize {
+        self.steps.0 + self.steps.1
+    }
 }
 
-fn part_2(input: &str) -> Result<()> {
-    let coordinates = parse_input(input)?;
+// Every wire starts from the central port, in (0, 0)
+struct Wires {
+    count: usize,
+    segments: Vec<Segment>,
+}
 
-    let mut min_step = ::std::usize::MAX;
-    use std::cmp;
+impl Wires {
+    // Every point where two different wires meet, except the central port. A wire crossing itself
+    // is not a crossing, but only the first time it reaches a point counts for its steps
+    fn crossings(&self) -> Result<Vec<Crossing>> {
+        if self.count < 2 {
+            err!(
+                "Need at least two wires to find crossings, found {}",
+                self.count
+            )
+        }
+
+        let mut steps: HashMap<(Point, usize, usize), (usize, usize)> = HashMap::new();
+        let mut add = |point: Point, first: &Segment, second: &Segment| {
+            if first.wire == second.wire || point == (0, 0) {
+                return;
+            }
+            let (first, second) = if first.wire < second.wire {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            let reached = (first.steps_to(point), second.steps_to(point));
+            let best = steps
+                .entry((point, first.wire, second.wire))
+                .or_insert(reached);
+            *best = (best.0.min(reached.0), best.1.min(reached.1));
+        };
 
-    for wires in coordinates.values() {
-        if wires.0.len() > 1 {
-            min_step = cmp::min(min_step, wires.1)
+        for (horizontal, vertical, point) in self.perpendicular_intersections() {
+            add(point, horizontal, vertical);
         }
+        for (first, second, point) in self.collinear_overlaps() {
+            add(point, first, second);
+        }
+
+        let mut crossings: Vec<Crossing> = steps
+            .into_iter()
+            .map(|((point, first, second), steps)| Crossing {
+                point,
+                wires: (first, second),
+                steps,
+            })
+            .collect();
+        crossings.sort_by_key(|crossing| (crossing.point, crossing.wires));
+        Ok(crossings)
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", min_step)?;
+    // Sweep-line from left to right : horizontal segments are active between their two ends, and
+    // every vertical segment met looks for the active ones within its vertical bounds
+    fn perpendicular_intersections(&self) -> Vec<(&Segment, &Segment, Point)> {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        enum Event {
+            // Bounds are included, so segments are added before and removed after queries
+            Insert,
+            Query,
+            Remove,
+        }
 
-    Ok(())
-}
+        let mut events: Vec<(i64, Event, usize)> = vec![];
+        for (i, segment) in self.segments.iter().enumerate() {
+            let (coordinate, min, max) = segment.line();
+            match segment.orientation() {
+                Orientation::Horizontal => {
+                    events.push((min, Event::Insert, i));
+                    events.push((max, Event::Remove, i));
+                }
+                Orientation::Vertical => events.push((coordinate, Event::Query, i)),
+            }
+        }
+        events.sort_unstable();
 
-fn add_coordinates(
-    c: &mut HashMap<(i64, i64), WiresStepCount>,
-    coord: (i64, i64),
-    wire_number: usize,
-    current_step: usize,
-) {
-    match c.get_mut(&coord) {
-        Some(wires) => {
-            if !wires.0.contains(&wire_number) {
-                wires.0.push(wire_number);
-                wires.1 += current_step;
+        let mut intersections = vec![];
+        let mut active: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+        for (x, event, i) in events {
+            let segment = &self.segments[i];
+            match event {
+                Event::Insert => active.entry(segment.start.1).or_default().push(i),
+                Event::Remove => {
+                    if let Some(horizontals) = active.get_mut(&segment.start.1) {
+                        horizontals.retain(|&horizontal| horizontal != i);
+                        if horizontals.is_empty() {
+                            active.remove(&segment.start.1);
+                        }
+                    }
+                }
+                Event::Query => {
+                    let (_, min, max) = segment.line();
+                    for (&y, horizontals) in active.range(min..=max) {
+                        for &horizontal in horizontals {
+                            intersections.push((&self.segments[horizontal], segment, (x, y)));
+                        }
+                    }
+                }
             }
         }
-        None => {
-            c.insert(coord, (vec![wire_number], current_step));
+        intersections
+    }
+
+    // Segments lying on the same line share every point of their overlap
+    fn collinear_overlaps(&self) -> Vec<(&Segment, &Segment, Point)> {
+        let mut lines: HashMap<(bool, i64), Vec<&Segment>> = HashMap::new();
+        for segment in &self.segments {
+            let (coordinate, _, _) = segment.line();
+            let horizontal = segment.orientation() == Orientation::Horizontal;
+            lines
+                .entry((horizontal, coordinate))
+                .or_default()
+                .push(segment);
         }
+
+        let mut overlaps = vec![];
+        for ((horizontal, coordinate), mut segments) in lines {
+            segments.sort_by_key(|segment| segment.line().1);
+            for (i, first) in segments.iter().enumerate() {
+                let (_, _, first_max) = first.line();
+                for second in &segments[i + 1..] {
+                    let (_, second_min, second_max) = second.line();
+                    if second_min > first_max {
+                        break;
+                    }
+                    for position in second_min..=first_max.min(second_max) {
+                        let point = if horizontal {
+                            (position, coordinate)
+                        } else {
+                            (coordinate, position)
+                        };
+                        overlaps.push((*first, *second, point));
+                    }
+                }
+            }
+        }
+        overlaps
+    }
+}
+
+fn main() -> Result<()> {
+    // Every crossing can be listed : cargo run --release -- --crossings
+    let options = Options::from_env(&[Flag::Switch("--crossings")])?;
+    if options.has("--crossings") {
+        return list_crossings(&options);
+    }
+
+    day().run_with(&options)
+}
+
+fn day() -> Day<Wires> {
+    Day::new(parse_input).part(part_1).part(part_2)
+}
+
+fn list_crossings(options: &Options) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let wires = parse_input(&input)?;
+
+    let mut stdout = io::stdout();
+    for crossing in wires.crossings()? {
+        writeln!(
+            stdout,
+            "Wires {} and {} cross at {:?} : distance {}, steps {} + {} = {}",
+            crossing.wires.0,
+            crossing.wires.1,
+            crossing.point,
+            crossing.distance(),
+            crossing.steps.0,
+            crossing.steps.1,
+            crossing.combined_steps()
+        )?;
+    }
+    Ok(())
+}
+
+fn part_1(wires: &Wires) -> Result<u64> {
+    match wires.crossings()?.iter().map(Crossing::distance).min() {
+        Some(distance) => Ok(distance),
+        None => err!("Wires never cross each other"),
+    }
+}
+
+fn part_2(wires: &Wires) -> Result<usize> {
+    match wires
+        .crossings()?
+        .iter()
+        .map(Crossing::combined_steps)
+        .min()
+    {
+        Some(steps) => Ok(steps),
+        None => err!("Wires never cross each other"),
     }
 }
 
-fn parse_input(input: &str) -> Result<HashMap<(i64, i64), WiresStepCount>> {
+fn parse_input(input: &str) -> Result<Wires> {
     lazy_static! {
         static ref DAY_03_REGEX: Regex = Regex::new("^(?P<direction>[RLUD])(?P<distance>[0-9]+)$")
             .expect("Invalid DAY_03_REGEX!");
     }
 
-    // Keep in memory all coordinates where wires appear and their minimum step_count sum
-    let mut coordinates: HashMap<(i64, i64), WiresStepCount> = HashMap::new();
-
-    for (wire_number, line) in input.lines().enumerate() {
-        let mut current_x = 0;
-        let mut current_y = 0;
-        let mut current_step = 0;
+    let mut segments = vec![];
+    let mut count = 0;
+    for (wire, line) in input.lines().enumerate() {
+        let mut current = (0, 0);
+        let mut steps = 0;
 
         for path in line.split(',') {
-            match DAY_03_REGEX.captures(&path) {
-                None => {
-                    err(&format!("Invalid input path found : {}", &path))?;
-                }
-                Some(cap) => {
-                    let distance: i64 = cap["distance"].parse()?;
-                    match &cap["direction"] {
-                        "R" => {
-                            for _ in 0..distance {
-                                current_x += 1;
-                                current_step += 1;
-                                add_coordinates(
-                                    &mut coordinates,
-                                    (current_x, current_y),
-                                    wire_number,
-                                    current_step,
-                                );
-                            }
-                        }
-                        "L" => {
-                            for _ in 0..distance {
-                                current_x -= 1;
-                                current_step += 1;
-                                add_coordinates(
-                                    &mut coordinates,
-                                    (current_x, current_y),
-                                    wire_number,
-                                    current_step,
-                                );
-                            }
-                        }
-                        "U" => {
-                            for _ in 0..distance {
-                                current_y += 1;
-                                current_step += 1;
-                                add_coordinates(
-                                    &mut coordinates,
-                                    (current_x, current_y),
-                                    wire_number,
-                                    current_step,
-                                );
-                            }
-                        }
-                        "D" => {
-                            for _ in 0..distance {
-                                current_y -= 1;
-                                current_step += 1;
-                                add_coordinates(
-                                    &mut coordinates,
-                                    (current_x, current_y),
-                                    wire_number,
-                                    current_step,
-                                );
-                            }
-                        }
-                        _ => unreachable!(
-                            "DAY_03_REGEX shouldn't have captured this case : {}",
-                            &path
-                        ),
-                    }
-                }
+            let cap = match DAY_03_REGEX.captures(path) {
+                Some(cap) => cap,
+                None => err!("Invalid input path found : {}", &path),
+            };
+            let distance: i64 = cap["distance"].parse()?;
+            let end = match &cap["direction"] {
+                "R" => (current.0 + distance, current.1),
+                "L" => (current.0 - distance, current.1),
+                "U" => (current.0, current.1 + distance),
+                "D" => (current.0, current.1 - distance),
+                _ => unreachable!("DAY_03_REGEX shouldn't have captured this case : {}", &path),
+            };
+
+            // Empty moves don't reach any new point
+            if distance > 0 {
+                segments.push(Segment {
+                    wire,
+                    start: current,
+                    end,
+                    steps,
+                });
             }
+            current = end;
+            steps += distance as usize;
         }
+        count += 1;
     }
 
-    Ok(coordinates)
+    Ok(Wires { count, segments })
 }
 
-fn err(s: &str) -> Result<()> {
-    Err(Box::<dyn Error>::from(s.to_string()))
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2, example_3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossings() -> Result<()> {
+        let wires = parse_input("R8,U5,L5,D3\nU7,R6,D4,L4")?;
+        assert_eq!(wires.count, 2);
+        assert_eq!(
+            wires.crossings()?,
+            vec![
+                Crossing {
+                    point: (3, 3),
+                    wires: (0, 1),
+                    steps: (20, 20)
+                },
+                Crossing {
+                    point: (6, 5),
+                    wires: (0, 1),
+                    steps: (15, 15)
+                }
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_crossings() -> Result<()> {
+        // The first wire crosses itself in (2, 0), which it reaches after 2 steps then after 10 steps
+        let wires = parse_input("R4,U2,L2,D4\nU1,R2,D3")?;
+        let crossings = wires.crossings()?;
+        assert!(crossings.iter().all(|crossing| crossing.wires == (0, 1)));
+        assert_eq!(
+            crossings
+                .iter()
+                .map(|crossing| (crossing.point, crossing.steps))
+                .collect::<Vec<_>>(),
+            vec![
+                ((2, -2), (12, 6)),
+                ((2, -1), (11, 5)),
+                ((2, 0), (2, 4)),
+                ((2, 1), (9, 3))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_several_wires_and_overlaps() -> Result<()> {
+        // The second wire runs along the first one, the third one crosses both of them
+        let wires = parse_input("R5\nU1,R2,D1,R2\nD2,R3,U4")?;
+        assert_eq!(wires.count, 3);
+        let crossings = wires.crossings()?;
+        assert_eq!(
+            crossings
+                .iter()
+                .map(|crossing| (crossing.point, crossing.wires))
+                .collect::<Vec<_>>(),
+            vec![
+                ((2, 0), (0, 1)),
+                ((3, 0), (0, 1)),
+                ((3, 0), (0, 2)),
+                ((3, 0), (1, 2)),
+                ((4, 0), (0, 1))
+            ]
+        );
+        assert_eq!(part_1(&wires)?, 2);
+        assert_eq!(part_2(&wires)?, 6);
+        assert!(part_1(&parse_input("R5\nU5")?).is_err());
+        assert!(part_2(&parse_input("R5")?).is_err());
+        Ok(())
+    }
 }
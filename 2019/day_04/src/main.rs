@@ -1,123 +1,362 @@
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
+}
 
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+// Run lengths are only told apart up to 3, longer runs behaving the same way
+const MAX_RUN: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PairRule {
+    // Two adjacent digits are the same
+    AtLeastOnePair,
+    // Two adjacent digits are the same without being part of a larger group
+    ExactlyOnePair,
 }
 
-fn part_1(input: &str) -> Result<()> {
-    let mut numbers: Vec<u64> = vec![];
-    for i in input.trim().split('-') {
-        if i.len() != 6 {
-            err("Codes should have a length of 6!")?;
+impl PairRule {
+    fn accepts(&self, run: usize) -> bool {
+        match self {
+            PairRule::AtLeastOnePair => run >= 2,
+            PairRule::ExactlyOnePair => run == 2,
         }
-        numbers.push(i.parse()?);
     }
+}
 
-    let minimum_code = *numbers.get(0).expect("Minimum code not found!");
-    let maximum_code = *numbers.get(1).expect("Maximum code not found!");
-    let mut current_code = minimum_code;
-    let mut result = 0;
+// State of a non-decreasing code being written : last digit, length of its run, and whether an
+// earlier run was accepted by the rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    digit: u32,
+    run: usize,
+    found: bool,
+}
 
-    while current_code <= maximum_code {
-        let str_code = current_code.to_string();
-        let digits_bytes = str_code.as_bytes();
-        let mut current_digit = digits_bytes[0];
-        let mut found_adjacent_digits = false;
-        let mut digits_always_increase = true;
+impl State {
+    fn start(digit: u32) -> Self {
+        State {
+            digit,
+            run: 1,
+            found: false,
+        }
+    }
 
-        for next_digit in digits_bytes.iter().skip(1) {
-            // Two conditions :
-            //   - Two adjacent digits are the same
-            //   - Digits always increase or stay the same
+    // Next state, if digits still never decrease
+    fn push(&self, digit: u32, rule: PairRule) -> Option<State> {
+        if digit < self.digit {
+            None
+        } else if digit == self.digit {
+            Some(State {
+                run: (self.run + 1).min(MAX_RUN),
+                ..*self
+            })
+        } else {
+            Some(State {
+                digit,
+                run: 1,
+                found: self.found || rule.accepts(self.run),
+            })
+        }
+    }
 
-            if *next_digit < current_digit {
-                digits_always_increase = false;
-                break;
-            }
-            if *next_digit == current_digit {
-                found_adjacent_digits = true;
+    fn accepted(&self, rule: PairRule) -> bool {
+        self.found || rule.accepts(self.run)
+    }
+}
+
+// Codes having exactly the same number of digits as both bounds, which are included
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PasswordRange {
+    base: u32,
+    minimum: Vec<u32>,
+    maximum: Vec<u32>,
+}
+
+impl PasswordRange {
+    fn new(minimum: &str, maximum: &str, base: u32) -> Result<Self> {
+        if !(2..=36).contains(&base) {
+            err!("Base should be between 2 and 36, found {}", base)
+        }
+        let digits = |code: &str| -> Result<Vec<u32>> {
+            match code.chars().map(|c| c.to_digit(base)).collect() {
+                Some(digits) => Ok(digits),
+                None => err!("Invalid code in base {} : {}", base, code),
             }
+        };
 
-            current_digit = *next_digit;
+        let (minimum, maximum) = (digits(minimum)?, digits(maximum)?);
+        if minimum.is_empty() || minimum.len() != maximum.len() {
+            err!("Codes should have the same non-zero length!")
         }
-        if found_adjacent_digits && digits_always_increase {
-            result += 1;
+        if minimum > maximum {
+            err!("Minimum code is greater than the maximum code!")
         }
+        Ok(PasswordRange {
+            base,
+            minimum,
+            maximum,
+        })
+    }
 
-        current_code += 1;
+    fn len(&self) -> usize {
+        self.minimum.len()
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
+    fn state_index(&self, state: &State) -> usize {
+        ((state.digit as usize * MAX_RUN) + state.run - 1) * 2 + state.found as usize
+    }
 
-    Ok(())
-}
+    // completions[remaining][state] : ways to add `remaining` digits to a code in `state` so that
+    // the rule accepts it
+    fn completions(&self, rule: PairRule) -> Vec<Vec<u128>> {
+        let states: Vec<State> = (0..self.base)
+            .flat_map(|digit| {
+                (1..=MAX_RUN).flat_map(move |run| {
+                    [false, true]
+                        .iter()
+                        .map(move |&found| State { digit, run, found })
+                })
+            })
+            .collect();
 
-fn part_2(input: &str) -> Result<()> {
-    let mut numbers: Vec<u64> = vec![];
-    for i in input.trim().split('-') {
-        if i.len() != 6 {
-            err("Codes should have a length of 6!")?;
+        let mut completions = vec![states
+            .iter()
+            .map(|state| state.accepted(rule) as u128)
+            .collect::<Vec<u128>>()];
+        for remaining in 1..self.len() {
+            let previous = &completions[remaining - 1];
+            let current = states
+                .iter()
+                .map(|state| {
+                    (state.digit..self.base)
+                        .filter_map(|digit| state.push(digit, rule))
+                        .map(|next| previous[self.state_index(&next)])
+                        .sum()
+                })
+                .collect();
+            completions.push(current);
         }
-        numbers.push(i.parse()?);
+        completions
     }
 
-    let minimum_code = *numbers.get(0).expect("Minimum code not found!");
-    let maximum_code = *numbers.get(1).expect("Maximum code not found!");
-    let mut current_code = minimum_code;
-    let mut result = 0;
+    // Accepted codes smaller than or equal to the bound : every code is a prefix of the bound, then
+    // a smaller digit, then anything
+    fn count_up_to(&self, bound: &[u32], rule: PairRule, completions: &[Vec<u128>]) -> u128 {
+        let mut count = 0;
+        let mut tight: Option<State> = None;
+
+        for (i, &bound_digit) in bound.iter().enumerate() {
+            let remaining = bound.len() - i - 1;
+            let first = tight.map_or(0, |state| state.digit);
+            for digit in first..bound_digit {
+                let next = match tight {
+                    Some(state) => state.push(digit, rule),
+                    None => Some(State::start(digit)),
+                };
+                if let Some(next) = next {
+                    count += completions[remaining][self.state_index(&next)];
+                }
+            }
+
+            tight = match tight {
+                Some(state) => match state.push(bound_digit, rule) {
+                    Some(next) => Some(next),
+                    None => return count,
+                },
+                None => Some(State::start(bound_digit)),
+            };
+        }
+
+        count + tight.map_or(0, |state| state.accepted(rule) as u128)
+    }
 
-    while current_code <= maximum_code {
-        let str_code = current_code.to_string();
-        let digits_bytes = str_code.as_bytes();
-        let mut current_digit = digits_bytes[0];
-        let mut found_adjacent_digits = false;
-        let mut digits_always_increase = true;
-        let mut current_repeated_digit = 1;
+    fn is_accepted(&self, code: &[u32], rule: PairRule) -> bool {
+        let mut state = State::start(code[0]);
+        for &digit in &code[1..] {
+            state = match state.push(digit, rule) {
+                Some(state) => state,
+                None => return false,
+            };
+        }
+        state.accepted(rule)
+    }
 
-        for next_digit in digits_bytes.iter().skip(1) {
-            // Two conditions :
-            //   - At least one group of at most two adjacent digits are the same
-            //   - Digits always increase or stay the same
+    fn count(&self, rule: PairRule) -> u128 {
+        let completions = self.completions(rule);
+        self.count_up_to(&self.maximum, rule, &completions)
+            - self.count_up_to(&self.minimum, rule, &completions)
+            + self.is_accepted(&self.minimum, rule) as u128
+    }
 
-            if *next_digit < current_digit {
-                digits_always_increase = false;
+    fn codes(&self, rule: PairRule) -> Codes<'_> {
+        // Smallest non-decreasing code not smaller than the minimum
+        let mut digits = self.minimum.clone();
+        for i in 1..digits.len() {
+            if digits[i] < digits[i - 1] {
+                let digit = digits[i - 1];
+                digits[i..].iter_mut().for_each(|d| *d = digit);
                 break;
             }
-            if *next_digit == current_digit {
-                current_repeated_digit += 1;
-            } else {
-                if current_repeated_digit == 2 {
-                    found_adjacent_digits = true;
-                }
-                current_repeated_digit = 1;
-            }
+        }
 
-            current_digit = *next_digit;
+        Codes {
+            range: self,
+            rule,
+            next: Some(digits),
         }
-        if current_repeated_digit == 2 {
-            found_adjacent_digits = true;
+    }
+}
+
+// Accepted codes in increasing order, only going through non-decreasing codes
+struct Codes<'a> {
+    range: &'a PasswordRange,
+    rule: PairRule,
+    next: Option<Vec<u32>>,
+}
+
+impl Codes<'_> {
+    // Next non-decreasing code : increase the last digit which can be, copying it on its right
+    fn advance(&mut self) {
+        let base = self.range.base;
+        if let Some(digits) = self.next.as_mut() {
+            match digits.iter().rposition(|&d| d + 1 < base) {
+                Some(i) => {
+                    let digit = digits[i] + 1;
+                    digits[i..].iter_mut().for_each(|d| *d = digit);
+                }
+                None => self.next = None,
+            }
         }
+    }
+}
+
+impl Iterator for Codes<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let digits = self.next.clone()?;
+            if digits > self.range.maximum {
+                self.next = None;
+                return None;
+            }
+            self.advance();
 
-        if found_adjacent_digits && digits_always_increase {
-            result += 1;
+            if self.range.is_accepted(&digits, self.rule) {
+                return Some(
+                    digits
+                        .iter()
+                        .filter_map(|&d| std::char::from_digit(d, self.range.base))
+                        .collect(),
+                );
+            }
         }
+    }
+}
+
+fn main() -> Result<()> {
+    // Codes can be written in another base : cargo run --release -- [--base 10]
+    // and the codes of a part listed : cargo run --release -- --list 1|2
+    let options = Options::from_env(&[Flag::Value("--base"), Flag::Value("--list")])?;
+    let base = options.parse_value::<u32>("--base")?.unwrap_or(10);
+    if let Some(part) = options.value("--list") {
+        let rule = match part {
+            "1" => PairRule::AtLeastOnePair,
+            "2" => PairRule::ExactlyOnePair,
+            _ => err!("--list expects a part, 1 or 2"),
+        };
+        return list(&options, base, rule);
+    }
+
+    day(base).run_with(&options)
+}
 
-        current_code += 1;
+fn day(base: u32) -> Day<PasswordRange> {
+    Day::new(move |input| parse_input(input, base))
+        .part(|range| Ok(part_1(range)))
+        .part(|range| Ok(part_2(range)))
+}
+
+fn parse_input(input: &str, base: u32) -> Result<PasswordRange> {
+    match input.trim().split_once('-') {
+        Some((minimum, maximum)) => PasswordRange::new(minimum, maximum, base),
+        None => err!("Input should be a range of codes : minimum-maximum"),
     }
+}
 
-    writeln!(io::stdout(), "Part 2 : {}", result)?;
+fn list(options: &Options, base: u32, rule: PairRule) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let range = parse_input(&input, base)?;
 
+    let mut stdout = io::stdout();
+    for code in range.codes(rule) {
+        writeln!(stdout, "{}", code)?;
+    }
     Ok(())
 }
 
-fn err(s: &str) -> Result<()> {
-    Err(Box::<dyn Error>::from(s.to_string()))
+fn part_1(range: &PasswordRange) -> u128 {
+    range.count(PairRule::AtLeastOnePair)
+}
+
+fn part_2(range: &PasswordRange) -> u128 {
+    range.count(PairRule::ExactlyOnePair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(code: &str, rule: PairRule) -> bool {
+        let range = PasswordRange::new(code, code, 10).unwrap();
+        range.count(rule) == 1 && range.codes(rule).collect::<Vec<String>>() == vec![code]
+    }
+
+    #[test]
+    fn test_rules() {
+        assert!(accepted("111111", PairRule::AtLeastOnePair));
+        assert!(!accepted("223450", PairRule::AtLeastOnePair));
+        assert!(!accepted("123789", PairRule::AtLeastOnePair));
+        assert!(accepted("112233", PairRule::ExactlyOnePair));
+        assert!(!accepted("123444", PairRule::ExactlyOnePair));
+        assert!(accepted("111122", PairRule::ExactlyOnePair));
+    }
+
+    #[test]
+    fn test_count_matches_codes() -> Result<()> {
+        for (minimum, maximum, base) in &[
+            ("100000", "999999", 10),
+            ("256310", "732736", 10),
+            ("0000", "2222", 3),
+            ("0a1", "f3e", 16),
+            ("1", "9", 10),
+        ] {
+            let range = PasswordRange::new(minimum, maximum, *base)?;
+            for &rule in &[PairRule::AtLeastOnePair, PairRule::ExactlyOnePair] {
+                let codes: Vec<String> = range.codes(rule).collect();
+                assert_eq!(range.count(rule), codes.len() as u128);
+                assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+                assert!(codes
+                    .iter()
+                    .all(|code| code.as_str() >= *minimum && code.as_str() <= *maximum));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_codes() -> Result<()> {
+        // With only 10 different digits, every non-decreasing code of 30 digits has a pair
+        let range = PasswordRange::new(&"0".repeat(30), &"9".repeat(30), 10)?;
+        assert_eq!(part_1(&range), 211915132);
+        assert!(part_2(&range) < part_1(&range));
+        assert!(parse_input("12-345", 10).is_err());
+        assert!(parse_input("345-123", 10).is_err());
+        Ok(())
+    }
 }
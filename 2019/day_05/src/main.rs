@@ -1,18 +1,16 @@
+use aoc_runner::Day;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<i64> {
     let mut steps: Vec<i64> = vec![];
 
     for step in input.trim().split(',') {
@@ -22,12 +20,10 @@ fn part_1(input: &str) -> Result<()> {
     let system_id = 1;
     let result = execute_intcode(steps, system_id)?;
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
-
-    Ok(())
+    Ok(result)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<i64> {
     let mut steps: Vec<i64> = vec![];
 
     for step in input.trim().split(',') {
@@ -37,9 +33,7 @@ fn part_2(input: &str) -> Result<()> {
     let system_id = 5;
     let result = execute_intcode(steps, system_id)?;
 
-    writeln!(io::stdout(), "Part 2 : {}", result)?;
-
-    Ok(())
+    Ok(result)
 }
 
 fn execute_intcode(mut steps: Vec<i64>, system_id: i64) -> Result<i64> {
@@ -85,7 +79,8 @@ fn execute_intcode(mut steps: Vec<i64>, system_id: i64) -> Result<i64> {
             }
             5 => {
                 let first_param = get_parameter(&steps, current_step, 1, immediate_mode_1)?;
-                let second_param = get_parameter(&steps, current_step, 2, immediate_mode_2)? as usize;
+                let second_param =
+                    get_parameter(&steps, current_step, 2, immediate_mode_2)? as usize;
 
                 if first_param != 0 {
                     current_step = second_param;
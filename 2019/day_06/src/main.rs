@@ -1,121 +1,237 @@
+use aoc_runner::{Day, Flag, Options};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
-use std::io::{self, Read, Write};
-
-use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
+}
 
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+// Objects are interned, every object being identified by its index in `names`
+struct OrbitTree {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    parents: Vec<Option<usize>>,
+    depths: Vec<usize>,
+    root: usize,
 }
 
-fn part_1(input: &str) -> Result<()> {
-    let mut orbits: HashMap<String, Vec<String>> = HashMap::new();
+impl OrbitTree {
+    fn id(&self, name: &str) -> Result<usize> {
+        match self.ids.get(name) {
+            Some(&id) => Ok(id),
+            None => err!("Unknown object : {}", name),
+        }
+    }
 
-    for line in input.lines() {
-        let o: Vec<&str> = line.split(')').collect();
-        let orbitee = o.get(0).expect("Orbitee code not found!").to_string();
-        let orbiter = o.get(1).expect("Orbiter code not found!").to_string();
+    // Number of direct and indirect orbits of an object
+    fn depth(&self, name: &str) -> Result<usize> {
+        Ok(self.depths[self.id(name)?])
+    }
 
-        match orbits.get_mut(&orbitee) {
-            Some(orbit) => {
-                orbit.push(orbiter);
-            }
-            None => {
-                orbits.insert(orbitee, vec![orbiter]);
-            }
-        }
+    fn total_orbits(&self) -> usize {
+        self.depths.iter().sum()
     }
 
-    let mut result = 0;
+    fn lowest_common_ancestor(&self, first: &str, second: &str) -> Result<&str> {
+        let (mut first, mut second) = (self.id(first)?, self.id(second)?);
 
-    let mut current_orbit_distance = 1;
-    let mut orbits_to_check: Vec<String> = vec!["COM".to_string()];
-    loop {
-        let mut next_orbits_to_check: Vec<String> = vec![];
-        while let Some(current_orbit) =  orbits_to_check.pop() {
-            if let Some(o) = orbits.get_mut(&current_orbit) {
-                result += o.len() * current_orbit_distance;
-                next_orbits_to_check.extend_from_slice(&o);
-            }
+        while self.depths[first] > self.depths[second] {
+            first = self.parents[first].unwrap_or(self.root);
         }
-
-        current_orbit_distance += 1;
-        orbits_to_check = next_orbits_to_check;
-        if orbits_to_check.is_empty() {
-            break;
+        while self.depths[second] > self.depths[first] {
+            second = self.parents[second].unwrap_or(self.root);
+        }
+        while first != second {
+            first = self.parents[first].unwrap_or(self.root);
+            second = self.parents[second].unwrap_or(self.root);
         }
-    }
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
-    Ok(())
-}
+        Ok(&self.names[first])
+    }
 
-fn part_2(input: &str) -> Result<()> {
-    let mut orbits: HashMap<String, Vec<String>> = HashMap::new();
+    // Number of edges between two objects
+    fn distance(&self, first: &str, second: &str) -> Result<usize> {
+        let ancestor = self.lowest_common_ancestor(first, second)?;
+        Ok(self.depth(first)? + self.depth(second)? - 2 * self.depth(ancestor)?)
+    }
 
-    let mut current_distance = 0;
+    // Number of orbital transfers needed to go from the object `first` orbits to the one `second` orbits
+    fn transfers(&self, first: &str, second: &str) -> Result<usize> {
+        let mut parents = vec![];
+        for name in &[first, second] {
+            match self.parents[self.id(name)?] {
+                Some(parent) => parents.push(self.names[parent].as_str()),
+                None => err!("{} does not orbit anything", name),
+            }
+        }
+        self.distance(parents[0], parents[1])
+    }
+}
 
-    for line in input.lines() {
-        let o: Vec<&str> = line.split(')').collect();
-        let orbitee = o.get(0).expect("Orbitee code not found!").to_string();
-        let orbiter = o.get(1).expect("Orbiter code not found!").to_string();
+impl TryFrom<&str> for OrbitTree {
+    type Error = Box<dyn Error>;
 
-        match orbits.get_mut(&orbitee) {
-            Some(orbit) => {
-                orbit.push(orbiter.clone());
+    fn try_from(input: &str) -> Result<Self> {
+        let mut names: Vec<String> = vec![];
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut parents: Vec<Option<usize>> = vec![];
+        let mut intern = |name: &str, parents: &mut Vec<Option<usize>>| -> usize {
+            if let Some(&id) = ids.get(name) {
+                return id;
             }
-            None => {
-                orbits.insert(orbitee.clone(), vec![orbiter.clone()]);
+            names.push(name.to_string());
+            ids.insert(name.to_string(), names.len() - 1);
+            parents.push(None);
+            names.len() - 1
+        };
+
+        for line in input.lines() {
+            let (orbitee, orbiter) = match line.trim().split_once(')') {
+                Some((orbitee, orbiter)) if !orbitee.is_empty() && !orbiter.is_empty() => {
+                    (orbitee, orbiter)
+                }
+                _ => err!("Couldn't parse orbit : {}", line),
+            };
+
+            let orbitee = intern(orbitee, &mut parents);
+            let orbiter_id = intern(orbiter, &mut parents);
+            if let Some(parent) = parents[orbiter_id] {
+                if parent != orbitee {
+                    err!("{} orbits two different objects", orbiter)
+                }
             }
+            parents[orbiter_id] = Some(orbitee);
         }
-        match orbits.get_mut(&orbiter) {
-            Some(orbit) => {
-                orbit.push(orbitee);
+
+        let roots: Vec<usize> = (0..names.len())
+            .filter(|&id| parents[id].is_none())
+            .collect();
+        let root = match roots.as_slice() {
+            [root] => *root,
+            [] => err!("Orbit map has no root, every object orbits another one"),
+            _ => err!(
+                "Orbit map has several roots : {}",
+                roots
+                    .iter()
+                    .map(|&id| names[id].as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        };
+
+        // Walking down from the root, objects never reached are orbiting in a cycle
+        let mut children: Vec<Vec<usize>> = vec![vec![]; names.len()];
+        for (id, parent) in parents.iter().enumerate() {
+            if let Some(parent) = parent {
+                children[*parent].push(id);
             }
-            None => {
-                orbits.insert(orbiter, vec![orbitee]);
+        }
+        let mut depths: Vec<Option<usize>> = vec![None; names.len()];
+        depths[root] = Some(0);
+        let mut to_visit = vec![root];
+        while let Some(id) = to_visit.pop() {
+            for &child in &children[id] {
+                depths[child] = depths[id].map(|depth| depth + 1);
+                to_visit.push(child);
             }
         }
+        if let Some(id) = depths.iter().position(Option::is_none) {
+            err!("{} is part of an orbit cycle", names[id])
+        }
+
+        let depths = depths.into_iter().flatten().collect();
+        Ok(OrbitTree {
+            names,
+            ids,
+            parents,
+            depths,
+            root,
+        })
     }
+}
 
-    let mut orbits_checked: HashSet<String> = HashSet::new();
-    orbits_checked.insert("YOU".to_string());
+fn main() -> Result<()> {
+    // Arbitrary pairs of objects can be queried : cargo run --release -- --query YOU SAN [--query A B...]
+    let options = Options::from_env(&[Flag::Values("--query", 2)])?;
+    let queries: Vec<(&str, &str)> = options
+        .values("--query")
+        .map(|objects| (objects[0].as_str(), objects[1].as_str()))
+        .collect();
+    if !queries.is_empty() {
+        return query(&options, &queries);
+    }
 
-    let mut orbits_to_check: Vec<String> = orbits["YOU"].clone();
-    'outer: loop {
-        let mut next_orbits_to_check: Vec<String> = vec![];
-        while let Some(current_orbit) =  orbits_to_check.pop() {
-            if current_orbit == "SAN" {
-                break 'outer;
-            }
+    day().run_with(&options)
+}
 
-            for i in &orbits[&current_orbit] {
-                if !orbits_checked.contains(i) {
-                    next_orbits_to_check.push(i.to_string());
-                    orbits_checked.insert(i.to_string());
-                }
-            }
-        }
+fn day() -> Day<OrbitTree> {
+    Day::new(parse_input)
+        .part(|orbits| Ok(part_1(orbits)))
+        .part(part_2)
+}
 
-        current_distance += 1;
-        orbits_to_check = next_orbits_to_check;
+fn parse_input(input: &str) -> Result<OrbitTree> {
+    OrbitTree::try_from(input)
+}
 
-        if orbits_to_check.is_empty() {
-            err("No path found!")?;
-            break;
-        }
+fn query(options: &Options, queries: &[(&str, &str)]) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let orbits = parse_input(&input)?;
+
+    for (first, second) in queries {
+        writeln!(
+            io::stdout(),
+            "{} / {} : depths {} / {}, common ancestor {}, distance {}, transfers {}",
+            first,
+            second,
+            orbits.depth(first)?,
+            orbits.depth(second)?,
+            orbits.lowest_common_ancestor(first, second)?,
+            orbits.distance(first, second)?,
+            orbits
+                .transfers(first, second)
+                .map_or_else(|e| e.to_string(), |transfers| transfers.to_string())
+        )?;
     }
-
-    writeln!(io::stdout(), "Part 2 : {}", current_distance - 1)?;
     Ok(())
 }
 
-fn err(s: &str) -> Result<()> {
-    Err(Box::<dyn Error>::from(s.to_string()))
+fn part_1(orbits: &OrbitTree) -> usize {
+    orbits.total_orbits()
+}
+
+fn part_2(orbits: &OrbitTree) -> Result<usize> {
+    orbits.transfers("YOU", "SAN")
+}
+
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries() -> Result<()> {
+        let orbits = parse_input("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L")?;
+        assert_eq!(orbits.depth("L")?, 7);
+        assert_eq!(orbits.lowest_common_ancestor("H", "I")?, "B");
+        assert_eq!(orbits.lowest_common_ancestor("L", "F")?, "E");
+        assert_eq!(orbits.distance("H", "I")?, 5);
+        assert_eq!(orbits.transfers("L", "I")?, 3);
+        assert!(orbits.depth("YOU").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_maps() {
+        assert!(parse_input("COM)B\nX)Y").is_err());
+        assert!(parse_input("COM)B\nB)C\nD)C").is_err());
+        assert!(parse_input("COM)B\nX)Y\nY)X").is_err());
+        assert!(parse_input("COM-B").is_err());
+    }
 }
@@ -1,18 +1,16 @@
+use aoc_runner::Day;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<i64> {
     let mut permutations: Vec<Vec<usize>> = vec![];
     let number_of_amplifiers = 5;
     let mut codes = vec![];
@@ -41,11 +39,10 @@ fn part_1(input: &str) -> Result<()> {
         max_result = std::cmp::max(current_result, max_result);
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", max_result)?;
-    Ok(())
+    Ok(max_result)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<i64> {
     let mut permutations: Vec<Vec<usize>> = vec![];
     let number_of_amplifiers = 5;
     let mut codes = vec![];
@@ -90,8 +87,7 @@ fn part_2(input: &str) -> Result<()> {
         max_result = std::cmp::max(current_result, max_result);
     }
 
-    writeln!(io::stdout(), "Part 2 : {}", max_result)?;
-    Ok(())
+    Ok(max_result)
 }
 
 fn generate_codes_permutations(
@@ -1,29 +1,34 @@
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    // The decoded image can also be saved as a file : cargo run --release -- --png < input/input.txt
+    let options = Options::from_env(&[Flag::Switch("--png")])?;
+    if options.has("--png") {
+        return write_png(&options);
+    }
 
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run_with(&options)
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<usize> {
     let mut pixels: Vec<char> = vec![];
     // Image is 25 pixels wide and 6 pixels tall
     let width = 25;
     let height = 6;
-    let mut min_zeroes = ::std::usize::MAX;
+    let mut min_zeroes = usize::MAX;
     let mut min_zeroes_layer = 0;
 
     for pixel in input.trim().chars() {
         pixels.push(pixel);
     }
-    if pixels.len() % (width * height) != 0 {
+    if !pixels.len().is_multiple_of(width * height) {
         err("Input length doesn't match assume width * height")?;
     }
 
@@ -48,11 +53,25 @@ fn part_1(input: &str) -> Result<()> {
             .filter(|&&v| v == '2')
             .count();
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
-    Ok(())
+    Ok(result)
 }
 
-fn part_2(input: &str) -> Result<()> {
+// Decoded image, drawn with `#` for white pixels
+fn part_2(input: &str) -> Result<String> {
+    let (result, width, _) = decode_image(input)?;
+    let mut text = String::new();
+    for line in result.chunks(width) {
+        text.push('\n');
+        text.extend(
+            line.iter()
+                .map(|&pixel| if pixel == 255 { '#' } else { ' ' }),
+        );
+    }
+    Ok(text)
+}
+
+// Grayscale pixels of the image, transparent ones being gray, along with its width and height
+fn decode_image(input: &str) -> Result<(Vec<u8>, usize, usize)> {
     let mut pixels: Vec<char> = vec![];
     // Image is 25 pixels wide and 6 pixels tall
     let width = 25;
@@ -62,7 +81,7 @@ fn part_2(input: &str) -> Result<()> {
     for pixel in input.trim().chars() {
         pixels.push(pixel);
     }
-    if pixels.len() % (width * height) != 0 {
+    if !pixels.len().is_multiple_of(width * height) {
         err("Input length doesn't match assume width * height")?;
     }
 
@@ -84,21 +103,24 @@ fn part_2(input: &str) -> Result<()> {
         result.push(127);
     }
 
+    Ok((result, width, height))
+}
+
+fn write_png(options: &Options) -> Result<()> {
     use image::png::PNGEncoder;
     use image::ColorType;
     use std::fs::File;
 
+    let input = aoc_runner::read_input(options)?;
+    let (result, width, height) = decode_image(&input)?;
+
     let image_file_path = format!("{}/part_2.png", env!("CARGO_MANIFEST_DIR"));
     let output = File::create(image_file_path.clone())?;
     let encoder = PNGEncoder::new(output);
 
     encoder.encode(&result, width as u32, height as u32, ColorType::Gray(8))?;
 
-    writeln!(
-        io::stdout(),
-        "Part 2 : To get result, open following image : \"{}\"",
-        image_file_path
-    )?;
+    writeln!(io::stdout(), "Image saved to \"{}\"", image_file_path)?;
     Ok(())
 }
 
@@ -1,21 +1,19 @@
 extern crate intcode_vm;
 
-use std::error::Error;
-use std::io::{self, Read, Write};
+use aoc_runner::Day;
 use intcode_vm::*;
+use std::error::Error;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<isize> {
     let mut vm = IntCodeVm::new(input)?;
     let mut result = 0;
     let mut vm_input = Some(1);
@@ -32,11 +30,10 @@ fn part_1(input: &str) -> Result<()> {
         }
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", result)?;
-    Ok(())
+    Ok(result)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<isize> {
     let mut vm = IntCodeVm::new(input)?;
     let mut result = 0;
     let mut vm_input = Some(2);
@@ -53,6 +50,5 @@ fn part_2(input: &str) -> Result<()> {
         }
     }
 
-    writeln!(io::stdout(), "Part 2 : {}", result)?;
-    Ok(())
+    Ok(result)
 }
@@ -1,209 +1,183 @@
-use std::collections::HashMap;
+use aoc_runner::Day;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
-type AsteroidPosition = (usize, usize);
-type AsteroidsByAngle = HashMap<String, Vec<AsteroidPosition>>;
+type Position = (i64, i64);
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-fn part_1(input: &str) -> Result<()> {
-    let asteroids = parse_input(input)?;
-    let (_best_position, asteroids_by_angle) = get_asteroids_by_angle_for_best_position(&asteroids);
-
-    writeln!(io::stdout(), "Part 1 : {}", asteroids_by_angle.len())?;
-    Ok(())
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-fn part_2(input: &str) -> Result<()> {
-    let asteroids = parse_input(input)?;
-    let (best_position, asteroids_by_angle) = get_asteroids_by_angle_for_best_position(&asteroids);
-
-    let asteroids_to_destroy = 200;
+// Direction from a station reduced by the gcd of its coordinates, so that asteroids hiding
+// each other share exactly the same direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Direction {
+    dx: i64,
+    dy: i64,
+}
 
-    if asteroids.len() < asteroids_to_destroy + 1 {
-        err(&format!(
-            "Need at least {} asteroids to execute day 10 part 2!",
-            asteroids_to_destroy + 1
-        ))?;
+impl Direction {
+    fn new(dx: i64, dy: i64) -> Self {
+        let g = gcd(dx, dy).max(1);
+        Direction {
+            dx: dx / g,
+            dy: dy / g,
+        }
     }
 
-    let resultant_asteroid = get_destroyed_asteroid_at_position(
-        asteroids_to_destroy,
-        best_position,
-        &asteroids_by_angle,
-    )?;
-
-    let result = resultant_asteroid.0 * 100 + resultant_asteroid.1;
+    // The laser starts pointing up and rotates clockwise, y growing downwards : the first half
+    // goes from up (included) to down (excluded), the second one from down to up
+    fn half(&self) -> u8 {
+        if self.dx > 0 || (self.dx == 0 && self.dy < 0) {
+            0
+        } else {
+            1
+        }
+    }
 
-    writeln!(io::stdout(), "Part 2 : {}", result)?;
-    Ok(())
+    fn cross(&self, other: &Direction) -> i64 {
+        self.dx * other.dy - self.dy * other.dx
+    }
 }
 
-fn parse_input(input: &str) -> Result<Vec<(f64, f64)>> {
-    let mut asteroids: Vec<(f64, f64)> = vec![];
+// Order in which the laser sweeps directions, exact since it only relies on integer arithmetic
+impl Ord for Direction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.half()
+            .cmp(&other.half())
+            .then_with(|| 0.cmp(&self.cross(other)))
+    }
+}
 
-    for (i, line) in input.lines().enumerate() {
-        for (j, c) in line.chars().enumerate() {
-            match c {
-                '.' => {
-                    // Empty space, nothing to do here
-                }
-                '#' => {
-                    asteroids.push((j as f64, i as f64));
-                }
-                _ => {
-                    err(&format!(
-                        "Invalid character `{}` found in position {},{}!",
-                        c, j, i
-                    ))?;
-                }
-            }
-        }
+impl PartialOrd for Direction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    Ok(asteroids)
+struct Asteroids {
+    positions: Vec<Position>,
 }
 
-fn get_asteroids_by_angle_for_best_position(
-    asteroids: &[(f64, f64)],
-) -> (AsteroidPosition, AsteroidsByAngle) {
-    let mut best_position: (f64, f64) = (0.0, 0.0);
-    let mut best_position_angles: AsteroidsByAngle = HashMap::new();
-
-    for i in 0..asteroids.len() {
-        let current_asteroid = asteroids[i];
-        let mut current_position_angles: AsteroidsByAngle = HashMap::new();
-
-        for (j, other_asteroid) in asteroids.iter().enumerate() {
-            if j != i {
-                // No need to compare an asteroid to itself
-                //                let other_asteroid = asteroids[j];
-                let delta_x = other_asteroid.0 - current_asteroid.0;
-                let delta_y = other_asteroid.1 - current_asteroid.1;
-                let angle = (delta_y.atan2(delta_x) * 180.0 / std::f64::consts::PI).to_string();
-
-                match current_position_angles.get_mut(&angle) {
-                    Some(positions) => {
-                        positions.push((other_asteroid.0 as usize, other_asteroid.1 as usize));
-                    }
-                    None => {
-                        current_position_angles.insert(
-                            angle,
-                            vec![(other_asteroid.0 as usize, other_asteroid.1 as usize)],
-                        );
-                    }
-                }
+impl Asteroids {
+    // Asteroids seen from a station, by direction in sweep order, nearest asteroids first
+    fn lines_of_sight(&self, station: Position) -> BTreeMap<Direction, Vec<Position>> {
+        let mut lines: BTreeMap<Direction, Vec<Position>> = BTreeMap::new();
+        for &asteroid in &self.positions {
+            if asteroid != station {
+                let direction = Direction::new(asteroid.0 - station.0, asteroid.1 - station.1);
+                lines.entry(direction).or_default().push(asteroid);
             }
         }
-        if current_position_angles.len() > best_position_angles.len() {
-            // Does not account for cases where two positions have same number of possible angles
-            best_position = current_asteroid;
-            best_position_angles = current_position_angles;
-        }
-    }
-
-    (
-        (best_position.0 as usize, best_position.1 as usize),
-        best_position_angles,
-    )
-}
 
-fn get_destroyed_asteroid_at_position(
-    number_to_destroy: usize,
-    current_position: AsteroidPosition,
-    asteroids_by_angle: &AsteroidsByAngle,
-) -> Result<AsteroidPosition> {
-    let sorted_angles = get_sorted_angles(&asteroids_by_angle)?;
-    let mut destroyed_asteroids_by_angle: HashMap<String, usize> = HashMap::new();
-    let mut asteroids_destroyed: usize = 0;
-
-    // First angle to check is -90 since laser's first position is up
-    let mut first_angle_to_check = -90.0;
-
-    let mut current_angle_position_to_check = 0;
-    while sorted_angles[current_angle_position_to_check] < first_angle_to_check {
-        current_angle_position_to_check += 1;
-        if current_angle_position_to_check > sorted_angles.len() {
-            // On the off chance no asteroids available on angles -90 to +180, restart search starting from -180
-            first_angle_to_check = -180.0;
-            current_angle_position_to_check = 0;
+        for asteroids in lines.values_mut() {
+            asteroids.sort_by_key(|asteroid| {
+                (asteroid.0 - station.0).abs() + (asteroid.1 - station.1).abs()
+            });
         }
+        lines
     }
 
-    loop {
-        let current_angle = sorted_angles[current_angle_position_to_check].to_string();
-        match destroyed_asteroids_by_angle.get_mut(&current_angle) {
-            Some(n) => {
-                if asteroids_by_angle[&current_angle].len() < *n {
-                    *n += 1;
-                    asteroids_destroyed += 1;
+    // Number of asteroids seen from the best stations, along with every station seeing that many
+    fn best_stations(&self) -> (usize, Vec<Position>) {
+        let mut best_stations = vec![];
+        let mut best_visible = 0;
+
+        for &station in &self.positions {
+            let visible = self.lines_of_sight(station).len();
+            match visible.cmp(&best_visible) {
+                Ordering::Greater => {
+                    best_visible = visible;
+                    best_stations = vec![station];
                 }
-            }
-            None => {
-                destroyed_asteroids_by_angle.insert(current_angle.clone(), 1);
-                asteroids_destroyed += 1;
+                Ordering::Equal => best_stations.push(station),
+                Ordering::Less => {}
             }
         }
 
-        if asteroids_destroyed == number_to_destroy {
-            let resultant_asteroid = get_nth_nearest_asteroid(
-                current_position,
-                destroyed_asteroids_by_angle[&current_angle],
-                asteroids_by_angle[&current_angle].clone(),
-            );
-            return Ok(resultant_asteroid);
-        }
+        (best_visible, best_stations)
+    }
+
+    // Every other asteroid, in the order the laser vaporizes them
+    fn vaporization_order(&self, station: Position) -> Vec<Position> {
+        let lines = self.lines_of_sight(station);
+        let mut order = Vec::with_capacity(self.positions.len());
 
-        // Check next available angle
-        current_angle_position_to_check += 1;
-        if current_angle_position_to_check >= sorted_angles.len() {
-            current_angle_position_to_check = 0;
+        let mut rotation = 0;
+        while order.len() + 1 < self.positions.len() {
+            for asteroids in lines.values() {
+                if let Some(&asteroid) = asteroids.get(rotation) {
+                    order.push(asteroid);
+                }
+            }
+            rotation += 1;
         }
+        order
     }
 }
 
-fn get_sorted_angles(asteroids_by_angle: &AsteroidsByAngle) -> Result<Vec<f64>> {
-    let mut angles: Vec<f64> = vec![];
-    for angle in asteroids_by_angle.keys() {
-        angles.push(angle.parse()?);
-    }
+fn main() -> Result<()> {
+    day().run()
+}
 
-    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+fn day() -> Day<Asteroids> {
+    Day::new(parse_input).part(part_1).part(part_2)
+}
 
-    Ok(angles)
+fn part_1(asteroids: &Asteroids) -> Result<usize> {
+    Ok(asteroids.best_stations().0)
 }
 
-fn get_nth_nearest_asteroid(
-    current_position: AsteroidPosition,
-    n: usize,
-    mut asteroids: Vec<AsteroidPosition>,
-) -> AsteroidPosition {
-    asteroids.sort_by(|&a, &b| {
-        ((((a.0 as f64 - current_position.0 as f64).powi(2))
-            + ((a.1 as f64 - current_position.1 as f64).powi(2)))
-        .sqrt())
-        .partial_cmp(
-            &((((b.0 as f64 - current_position.0 as f64).powi(2))
-                + ((b.1 as f64 - current_position.1 as f64).powi(2)))
-            .sqrt()),
-        )
-        .unwrap()
-    });
-
-    asteroids[n - 1]
+fn part_2(asteroids: &Asteroids) -> Result<i64> {
+    let asteroids_to_destroy = 200;
+
+    let station = match asteroids.best_stations().1.as_slice() {
+        [station] => *station,
+        [] => err!("Need at least one asteroid to build the station!"),
+        stations => err!(
+            "Several positions are best for the station : {:?}",
+            stations
+        ),
+    };
+
+    match asteroids
+        .vaporization_order(station)
+        .get(asteroids_to_destroy - 1)
+    {
+        Some(asteroid) => Ok(asteroid.0 * 100 + asteroid.1),
+        None => err!(
+            "Need at least {} asteroids to execute day 10 part 2!",
+            asteroids_to_destroy + 1
+        ),
+    }
 }
 
-fn err(s: &str) -> Result<()> {
-    Err(Box::<dyn Error>::from(s.to_string()))
+fn parse_input(input: &str) -> Result<Asteroids> {
+    let mut positions = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        for (j, c) in line.chars().enumerate() {
+            match c {
+                '.' => {
+                    // Empty space, nothing to do here
+                }
+                '#' => positions.push((j as i64, i as i64)),
+                _ => err!("Invalid character `{}` found in position {},{}!", c, j, i),
+            }
+        }
+    }
+
+    Ok(Asteroids { positions })
 }
 
 #[cfg(test)]
@@ -220,9 +194,7 @@ mod tests {
 ";
 
         let asteroids = parse_input(input).unwrap();
-        let (best_position, asteroids_by_angle) =
-            get_asteroids_by_angle_for_best_position(&asteroids);
-        assert_eq!((best_position, asteroids_by_angle.len()), ((3, 4), 8));
+        assert_eq!(asteroids.best_stations(), (8, vec![(3, 4)]));
     }
 
     #[test]
@@ -240,9 +212,7 @@ mod tests {
 ";
 
         let asteroids = parse_input(input).unwrap();
-        let (best_position, asteroids_by_angle) =
-            get_asteroids_by_angle_for_best_position(&asteroids);
-        assert_eq!((best_position, asteroids_by_angle.len()), ((5, 8), 33));
+        assert_eq!(asteroids.best_stations(), (33, vec![(5, 8)]));
     }
 
     #[test]
@@ -260,9 +230,7 @@ mod tests {
 ";
 
         let asteroids = parse_input(input).unwrap();
-        let (best_position, asteroids_by_angle) =
-            get_asteroids_by_angle_for_best_position(&asteroids);
-        assert_eq!((best_position, asteroids_by_angle.len()), ((1, 2), 35));
+        assert_eq!(asteroids.best_stations(), (35, vec![(1, 2)]));
     }
 
     #[test]
@@ -280,9 +248,7 @@ mod tests {
 ";
 
         let asteroids = parse_input(input).unwrap();
-        let (best_position, asteroids_by_angle) =
-            get_asteroids_by_angle_for_best_position(&asteroids);
-        assert_eq!((best_position, asteroids_by_angle.len()), ((6, 3), 41));
+        assert_eq!(asteroids.best_stations(), (41, vec![(6, 3)]));
     }
 
     #[test]
@@ -310,8 +276,47 @@ mod tests {
 ";
 
         let asteroids = parse_input(input).unwrap();
-        let (best_position, asteroids_by_angle) =
-            get_asteroids_by_angle_for_best_position(&asteroids);
-        assert_eq!((best_position, asteroids_by_angle.len()), ((11, 13), 210));
+        assert_eq!(asteroids.best_stations(), (210, vec![(11, 13)]));
+
+        let order = asteroids.vaporization_order((11, 13));
+        assert_eq!((order[0], order[1], order[2]), ((11, 12), (12, 1), (12, 2)));
+        assert_eq!((order[49], order[99]), ((16, 9), (10, 16)));
+        assert_eq!(
+            (order[198], order[199], order[200]),
+            ((9, 6), (8, 2), (10, 9))
+        );
+        assert_eq!((order[298], order.len()), ((11, 1), 299));
+        assert_eq!(part_2(&asteroids).unwrap(), 802);
+    }
+
+    #[test]
+    fn test_vaporization_order() {
+        let asteroids = parse_input(".#....#####...#..\n##...##.#####..##\n##...#...#.#####.\n..#.....#...###..\n..#.#.....#....##").unwrap();
+        let order = asteroids.vaporization_order((8, 3));
+        assert_eq!(
+            order[..9],
+            [
+                (8, 1),
+                (9, 0),
+                (9, 1),
+                (10, 0),
+                (9, 2),
+                (11, 1),
+                (12, 1),
+                (11, 2),
+                (15, 1)
+            ]
+        );
+        assert_eq!(order.last(), Some(&(14, 3)));
+    }
+
+    #[test]
+    fn test_tied_stations() {
+        // Both ends only see the middle asteroid, which sees both of them
+        let asteroids = parse_input("#.#.#").unwrap();
+        assert_eq!(asteroids.best_stations(), (2, vec![(2, 0)]));
+        let asteroids = parse_input("#\n.\n#").unwrap();
+        assert_eq!(asteroids.best_stations(), (1, vec![(0, 0), (0, 2)]));
+        assert!(part_2(&asteroids).is_err());
     }
 }
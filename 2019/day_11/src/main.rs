@@ -1,23 +1,24 @@
-extern crate intcode_vm;
-
+use aoc_runner::{Day, Flag, Options};
 use std::collections::HashMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
-
-use intcode_vm::*;
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    // The registration identifier can also be saved as an image : cargo run --release -- --png < input/input.txt
+    let options = Options::from_env(&[Flag::Switch("--png")])?;
+    if options.has("--png") {
+        return write_png(&options);
+    }
 
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run_with(&options)
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<usize> {
     let mut steps: Vec<i64> = vec![];
     for step in input.trim().split(',') {
         steps.push(step.parse()?);
@@ -25,11 +26,22 @@ fn part_1(input: &str) -> Result<()> {
 
     let painted_positions = get_painted_positions(steps, 0)?;
 
-    writeln!(io::stdout(), "Part 1 : {}", painted_positions.len())?;
-    Ok(())
+    Ok(painted_positions.len())
+}
+
+// Registration identifier, drawn with `#` for white panels
+fn part_2(input: &str) -> Result<String> {
+    let (pixels, width, _) = paint_registration(input)?;
+    let mut text = String::new();
+    for line in pixels.chunks(width) {
+        text.push('\n');
+        text.extend(line.iter().map(|&pixel| if pixel == 0 { ' ' } else { '#' }));
+    }
+    Ok(text)
 }
 
-fn part_2(input: &str) -> Result<()> {
+// Grayscale pixels of the painted panels, along with the width and height of the image
+fn paint_registration(input: &str) -> Result<(Vec<u8>, usize, usize)> {
     let mut steps: Vec<i64> = vec![];
     for step in input.trim().split(',') {
         steps.push(step.parse()?);
@@ -62,22 +74,24 @@ fn part_2(input: &str) -> Result<()> {
             }
     }
 
+    Ok((pixels, width, height))
+}
+
+fn write_png(options: &Options) -> Result<()> {
     use image::png::PNGEncoder;
     use image::ColorType;
     use std::fs::File;
 
+    let input = aoc_runner::read_input(options)?;
+    let (pixels, width, height) = paint_registration(&input)?;
+
     let image_file_path = format!("{}/part_2.png", env!("CARGO_MANIFEST_DIR"));
     let output = File::create(image_file_path.clone())?;
     let encoder = PNGEncoder::new(output);
 
     encoder.encode(&pixels, width as u32, height as u32, ColorType::Gray(8))?;
 
-    writeln!(
-        io::stdout(),
-        "Part 2 : To get result, open following image : \"{}\"",
-        image_file_path
-    )?;
-
+    writeln!(io::stdout(), "Image saved to \"{}\"", image_file_path)?;
     Ok(())
 }
 
@@ -2,203 +2,296 @@
 extern crate lazy_static;
 extern crate regex;
 
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::thread;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-fn part_1(input: &str) -> Result<()> {
-    let mut coordinates: Vec<(isize, isize, isize)> = parse_input(input)?;
-    let mut velocities: Vec<(isize, isize, isize)> =
-        coordinates.iter().map(|_| (0, 0, 0)).collect();
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Body {
+    position: Vec<i64>,
+    velocity: Vec<i64>,
+}
 
-    // Simulate 1000 steps
-    simulate_moon_motions(&mut coordinates, &mut velocities, 1000);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Energy {
+    potential: i64,
+    kinetic: i64,
+}
 
-    let total_energy = get_total_energy(&coordinates, &velocities);
+impl Energy {
+    fn total(&self) -> i64 {
+        self.potential * self.kinetic
+    }
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", total_energy)?;
-    Ok(())
+// State of every body along a single axis, axes being independent from each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Axis {
+    positions: Vec<i64>,
+    velocities: Vec<i64>,
 }
 
-fn part_2(input: &str) -> Result<()> {
-    let coordinates: Vec<(isize, isize, isize)> = parse_input(input)?;
-    let velocities: Vec<(isize, isize, isize)> = coordinates.iter().map(|_| (0, 0, 0)).collect();
+impl Axis {
+    fn step(&mut self) {
+        // Apply gravity to each pair of bodies and update the velocity
+        for i in 0..self.positions.len() {
+            for j in (i + 1)..self.positions.len() {
+                let pull = (self.positions[j] - self.positions[i]).signum();
+                self.velocities[i] += pull;
+                self.velocities[j] -= pull;
+            }
+        }
+        for (position, velocity) in self.positions.iter_mut().zip(&self.velocities) {
+            *position += velocity;
+        }
+    }
 
-    let result = get_first_repeated_state_step(coordinates, velocities);
+    // Every step can be undone, so the first state repeated is always the initial one
+    fn period(&self) -> u64 {
+        let mut axis = self.clone();
+        let mut steps = 0;
+        loop {
+            axis.step();
+            steps += 1;
+            if axis == *self {
+                return steps;
+            }
+        }
+    }
+}
 
-    writeln!(io::stdout(), "Part 2: {}", result)?;
-    Ok(())
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct System {
+    // Coordinate names, in input order, one per dimension
+    axes: Vec<String>,
+    bodies: Vec<Body>,
 }
 
-fn simulate_moon_motions(
-    coordinates: &mut Vec<(isize, isize, isize)>,
-    velocities: &mut Vec<(isize, isize, isize)>,
-    steps_to_simulate: usize,
-) {
-    for _ in 0..steps_to_simulate {
-        // Apply gravity to each pair of moon and update the velocity
-        for i in 0..(coordinates.len() - 1) {
-            for j in i..coordinates.len() {
-                // Update x velocities
-                if coordinates[i].0 < coordinates[j].0 {
-                    velocities[i].0 += 1;
-                    velocities[j].0 -= 1;
-                } else if coordinates[i].0 > coordinates[j].0 {
-                    velocities[i].0 -= 1;
-                    velocities[j].0 += 1;
-                }
+impl System {
+    fn axis(&self, axis: usize) -> Axis {
+        Axis {
+            positions: self.bodies.iter().map(|body| body.position[axis]).collect(),
+            velocities: self.bodies.iter().map(|body| body.velocity[axis]).collect(),
+        }
+    }
 
-                // Update y velocities
-                if coordinates[i].1 < coordinates[j].1 {
-                    velocities[i].1 += 1;
-                    velocities[j].1 -= 1;
-                } else if coordinates[i].1 > coordinates[j].1 {
-                    velocities[i].1 -= 1;
-                    velocities[j].1 += 1;
-                }
+    fn set_axis(&mut self, axis: usize, state: &Axis) {
+        for (i, body) in self.bodies.iter_mut().enumerate() {
+            body.position[axis] = state.positions[i];
+            body.velocity[axis] = state.velocities[i];
+        }
+    }
 
-                // Update z velocities
-                if coordinates[i].2 < coordinates[j].2 {
-                    velocities[i].2 += 1;
-                    velocities[j].2 -= 1;
-                } else if coordinates[i].2 > coordinates[j].2 {
-                    velocities[i].2 -= 1;
-                    velocities[j].2 += 1;
-                }
+    fn simulate(&mut self, steps: usize) {
+        for axis in 0..self.axes.len() {
+            let mut state = self.axis(axis);
+            for _ in 0..steps {
+                state.step();
             }
+            self.set_axis(axis, &state);
+        }
+    }
+
+    fn energies(&self) -> Vec<Energy> {
+        self.bodies
+            .iter()
+            .map(|body| Energy {
+                potential: body.position.iter().map(|p| p.abs()).sum(),
+                kinetic: body.velocity.iter().map(|v| v.abs()).sum(),
+            })
+            .collect()
+    }
+
+    fn total_energy(&self) -> i64 {
+        self.energies().iter().map(Energy::total).sum()
+    }
+
+    fn axis_periods(&self, parallel: bool) -> Vec<u64> {
+        if !parallel {
+            return (0..self.axes.len())
+                .map(|axis| self.axis(axis).period())
+                .collect();
         }
 
-        for (c, v) in coordinates.iter_mut().zip(velocities.iter()) {
-            c.0 += v.0;
-            c.1 += v.1;
-            c.2 += v.2;
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.axes.len())
+                .map(|axis| {
+                    let state = self.axis(axis);
+                    scope.spawn(move || state.period())
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Axis simulation panicked!"))
+                .collect()
+        })
+    }
+
+    // Steps before the whole system gets back to a previous state
+    fn period(&self, parallel: bool) -> Result<u128> {
+        let mut period: u128 = 1;
+        for axis_period in self.axis_periods(parallel) {
+            period = match lowest_common_multiple(period, axis_period as u128) {
+                Some(period) => period,
+                None => err!("System period overflows a u128"),
+            };
         }
+        Ok(period)
     }
 }
 
-fn parse_input(input: &str) -> Result<Vec<(isize, isize, isize)>> {
-    use regex::Regex;
+// Bodies are written one per line like `<x=-1, y=0, z=2>`. The first body gives the number of
+// dimensions and their names, every other body must use the same ones in the same order
+impl FromStr for System {
+    type Err = Box<dyn Error>;
 
-    lazy_static! {
-        static ref DAY_12_REGEX: Regex =
-            Regex::new("^<x=(?P<x>-?[0-9]+), y=(?P<y>-?[0-9]+), z=(?P<z>-?[0-9]+)>$")
-                .expect("Invalid DAY_12_REGEX!");
-    }
+    fn from_str(input: &str) -> Result<Self> {
+        use regex::Regex;
 
-    let mut coordinates: Vec<(isize, isize, isize)> = vec![];
+        lazy_static! {
+            static ref DAY_12_REGEX: Regex =
+                Regex::new("^<(?P<coordinates>[a-z]+=-?[0-9]+(, [a-z]+=-?[0-9]+)*)>$")
+                    .expect("Invalid DAY_12_REGEX!");
+        }
 
-    for line in input.lines() {
-        match DAY_12_REGEX.captures(&line) {
-            None => {
-                err(&format!("Invalid input coordinate found : {}", &line))?;
+        let mut axes: Option<Vec<String>> = None;
+        let mut bodies = vec![];
+        for line in input.lines() {
+            let cap = match DAY_12_REGEX.captures(line) {
+                Some(cap) => cap,
+                None => err!("Invalid input coordinate found : {}", &line),
+            };
+
+            let mut names = vec![];
+            let mut position = vec![];
+            for coordinate in cap["coordinates"].split(", ") {
+                if let Some((name, value)) = coordinate.split_once('=') {
+                    names.push(name.to_string());
+                    position.push(value.parse()?);
+                }
             }
-            Some(cap) => {
-                coordinates.push((cap["x"].parse()?, cap["y"].parse()?, cap["z"].parse()?))
+            match &axes {
+                None => axes = Some(names),
+                Some(axes) if *axes != names => err!(
+                    "Expected coordinates {}, found {} : {}",
+                    axes.join(", "),
+                    names.join(", "),
+                    line
+                ),
+                Some(_) => {}
             }
+            bodies.push(Body {
+                velocity: vec![0; position.len()],
+                position,
+            });
         }
-    }
 
-    Ok(coordinates)
+        match axes {
+            Some(axes) => Ok(System { axes, bodies }),
+            None => err!("Input is empty!"),
+        }
+    }
 }
 
-fn get_total_energy(
-    coordinates: &[(isize, isize, isize)],
-    velocities: &[(isize, isize, isize)],
-) -> isize {
-    let mut total_energy = 0;
-
-    for (c, v) in coordinates.iter().zip(velocities.iter()) {
-        total_energy += (c.0.abs() + c.1.abs() + c.2.abs()) * (v.0.abs() + v.1.abs() + v.2.abs());
+fn greatest_common_divisor(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
     }
-    total_energy
+    a
 }
 
-fn get_first_repeated_state_step(
-    mut coordinates: Vec<(isize, isize, isize)>,
-    mut velocities: Vec<(isize, isize, isize)>,
-) -> usize {
-    let mut steps_velocities_zero: (usize, usize, usize) = (0, 0, 0);
-
-    let mut current_step: usize = 0;
-    loop {
-        simulate_moon_motions(&mut coordinates, &mut velocities, 1);
-        current_step += 1;
-
-        // Check x velocities
-        if velocities[0].0 == 0
-            && velocities[1].0 == 0
-            && velocities[2].0 == 0
-            && velocities[3].0 == 0
-            && steps_velocities_zero.0 == 0
-        {
-            steps_velocities_zero.0 = current_step;
-        }
-        // Check y velocities
-        if velocities[0].1 == 0
-            && velocities[1].1 == 0
-            && velocities[2].1 == 0
-            && velocities[3].1 == 0
-            && steps_velocities_zero.1 == 0
-        {
-            steps_velocities_zero.1 = current_step;
-        }
-        // Check z velocities
-        if velocities[0].2 == 0
-            && velocities[1].2 == 0
-            && velocities[2].2 == 0
-            && velocities[3].2 == 0
-            && steps_velocities_zero.2 == 0
-        {
-            steps_velocities_zero.2 = current_step;
-        }
+fn lowest_common_multiple(a: u128, b: u128) -> Option<u128> {
+    (a / greatest_common_divisor(a, b)).checked_mul(b)
+}
 
-        if steps_velocities_zero.0 != 0
-            && steps_velocities_zero.1 != 0
-            && steps_velocities_zero.2 != 0
-        {
-            break;
-        }
+fn main() -> Result<()> {
+    // Steps of part 1 are configurable : cargo run --release -- [--steps 1000] [--parallel] [--energy]
+    let options = Options::from_env(&[
+        Flag::Value("--steps"),
+        Flag::Switch("--parallel"),
+        Flag::Switch("--energy"),
+    ])?;
+    let steps = options.parse_value::<usize>("--steps")?.unwrap_or(1000);
+    let parallel = options.has("--parallel");
+    if options.has("--energy") {
+        return report_energy(&options, steps);
     }
 
-    2 * get_lowest_common_multiple(
-        get_lowest_common_multiple(steps_velocities_zero.0, steps_velocities_zero.1),
-        steps_velocities_zero.2,
-    )
+    day(steps, parallel).run_with(&options)
 }
 
-fn get_lowest_common_multiple(a: usize, b: usize) -> usize {
-    (a * b) / get_greatest_common_divisor(a, b)
+fn day(steps: usize, parallel: bool) -> Day<System> {
+    Day::new(parse_input)
+        .part(move |system| Ok(part_1(system, steps)))
+        .part(move |system| part_2(system, parallel))
 }
 
-fn get_greatest_common_divisor(mut a: usize, mut b: usize) -> usize {
-    while a != b {
-        if a > b {
-            a -= b;
-        } else {
-            b -= a;
-        }
+fn parse_input(input: &str) -> Result<System> {
+    System::from_str(input)
+}
+
+fn report_energy(options: &Options, steps: usize) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let mut system = parse_input(&input)?;
+    system.simulate(steps);
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "After {} steps :", steps)?;
+    for (body, energy) in system.bodies.iter().zip(system.energies()) {
+        writeln!(
+            stdout,
+            "    pos={:?}, vel={:?} : pot {} * kin {} = {}",
+            body.position,
+            body.velocity,
+            energy.potential,
+            energy.kinetic,
+            energy.total()
+        )?;
     }
-    a
+    writeln!(stdout, "Total energy : {}", system.total_energy())?;
+    Ok(())
+}
+
+fn part_1(system: &System, steps: usize) -> i64 {
+    let mut system = system.clone();
+    system.simulate(steps);
+    system.total_energy()
 }
 
-fn err(s: &str) -> Result<()> {
-    Err(Box::<dyn Error>::from(s.to_string()))
+fn part_2(system: &System, parallel: bool) -> Result<u128> {
+    system.period(parallel)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn positions_and_velocities(system: &System) -> (Vec<[i64; 3]>, Vec<[i64; 3]>) {
+        let array = |values: &Vec<i64>| [values[0], values[1], values[2]];
+        (
+            system
+                .bodies
+                .iter()
+                .map(|body| array(&body.position))
+                .collect(),
+            system
+                .bodies
+                .iter()
+                .map(|body| array(&body.velocity))
+                .collect(),
+        )
+    }
+
     #[test]
     fn test_1() {
         let input = r"<x=-1, y=0, z=2>
@@ -207,46 +300,47 @@ mod tests {
 <x=3, y=5, z=-1>
 ";
 
-        let mut coordinates: Vec<(isize, isize, isize)> = parse_input(input).unwrap();
-        let mut velocities: Vec<(isize, isize, isize)> =
-            coordinates.iter().map(|_| (0, 0, 0)).collect();
+        let mut system = parse_input(input).unwrap();
 
-        simulate_moon_motions(&mut coordinates, &mut velocities, 0);
+        system.simulate(0);
         assert_eq!(
-            (&coordinates, &velocities),
+            positions_and_velocities(&system),
             (
-                &vec![(-1, 0, 2), (2, -10, -7), (4, -8, 8), (3, 5, -1)],
-                &vec![(0, 0, 0), (0, 0, 0), (0, 0, 0), (0, 0, 0)]
+                vec![[-1, 0, 2], [2, -10, -7], [4, -8, 8], [3, 5, -1]],
+                vec![[0, 0, 0], [0, 0, 0], [0, 0, 0], [0, 0, 0]]
             ),
             "Step 0"
         );
 
-        simulate_moon_motions(&mut coordinates, &mut velocities, 1);
+        system.simulate(1);
         assert_eq!(
-            (&coordinates, &velocities),
+            positions_and_velocities(&system),
             (
-                &vec![(2, -1, 1), (3, -7, -4), (1, -7, 5), (2, 2, 0)],
-                &vec![(3, -1, -1), (1, 3, 3), (-3, 1, -3), (-1, -3, 1),]
+                vec![[2, -1, 1], [3, -7, -4], [1, -7, 5], [2, 2, 0]],
+                vec![[3, -1, -1], [1, 3, 3], [-3, 1, -3], [-1, -3, 1]]
             ),
             "Step 1"
         );
 
-        simulate_moon_motions(&mut coordinates, &mut velocities, 1);
+        system.simulate(1);
         assert_eq!(
-            (&coordinates, &velocities),
+            positions_and_velocities(&system),
             (
-                &vec![(5, -3, -1), (1, -2, 2), (1, -4, -1), (1, -4, 2)],
-                &vec![(3, -2, -2), (-2, 5, 6), (0, 3, -6), (-1, -6, 2)]
+                vec![[5, -3, -1], [1, -2, 2], [1, -4, -1], [1, -4, 2]],
+                vec![[3, -2, -2], [-2, 5, 6], [0, 3, -6], [-1, -6, 2]]
             ),
             "Step 2"
         );
 
-        simulate_moon_motions(&mut coordinates, &mut velocities, 8);
+        system.simulate(8);
+        assert_eq!(system.total_energy(), 179, "Energy at step 10!");
         assert_eq!(
-            get_total_energy(&coordinates, &velocities),
-            179,
-            "Energy at step 10!"
-        )
+            system.energies()[0],
+            Energy {
+                potential: 6,
+                kinetic: 6
+            }
+        );
     }
 
     #[test]
@@ -257,12 +351,9 @@ mod tests {
 <x=3, y=5, z=-1>
 ";
 
-        let coordinates: Vec<(isize, isize, isize)> = parse_input(input).unwrap();
-        let velocities: Vec<(isize, isize, isize)> =
-            coordinates.iter().map(|_| (0, 0, 0)).collect();
-
+        let system = parse_input(input).unwrap();
         assert_eq!(
-            get_first_repeated_state_step(coordinates, velocities),
+            system.period(false).unwrap(),
             2772,
             "2772 steps to get to previous state!"
         )
@@ -276,14 +367,53 @@ mod tests {
 <x=9, y=-8, z=-3>
 ";
 
-        let coordinates: Vec<(isize, isize, isize)> = parse_input(input).unwrap();
-        let velocities: Vec<(isize, isize, isize)> =
-            coordinates.iter().map(|_| (0, 0, 0)).collect();
-
+        let system = parse_input(input).unwrap();
+        assert_eq!(part_1(&system, 100), 1940);
         assert_eq!(
-            get_first_repeated_state_step(coordinates, velocities),
+            system.period(true).unwrap(),
             4686774924,
             "4686774924 steps to get to previous state!"
         );
     }
+
+    #[test]
+    fn test_other_dimensions() {
+        // Same bodies as the first example, without z in 2D, with w mirroring x in 4D
+        let example =
+            parse_input("<x=-1, y=0, z=2>\n<x=2, y=-10, z=-7>\n<x=4, y=-8, z=8>\n<x=3, y=5, z=-1>")
+                .unwrap();
+        let mut plane = parse_input("<x=-1, y=0>\n<x=2, y=-10>\n<x=4, y=-8>\n<x=3, y=5>").unwrap();
+        let mut hyper = parse_input(
+            "<x=-1, y=0, z=2, w=-1>\n<x=2, y=-10, z=-7, w=2>\n<x=4, y=-8, z=8, w=4>\n<x=3, y=5, z=-1, w=3>",
+        )
+        .unwrap();
+        assert_eq!(plane.axes, vec!["x", "y"]);
+        assert_eq!(hyper.axes, vec!["x", "y", "z", "w"]);
+
+        let periods = example.axis_periods(false);
+        assert_eq!(plane.axis_periods(true), periods[..2].to_vec());
+        assert_eq!(
+            hyper.axis_periods(true),
+            [&periods[..], &periods[..1]].concat()
+        );
+        assert_eq!(hyper.period(false).unwrap(), 2772);
+
+        let mut example = example;
+        example.simulate(10);
+        plane.simulate(10);
+        hyper.simulate(10);
+        for ((body, planar), hyper) in example.bodies.iter().zip(&plane.bodies).zip(&hyper.bodies) {
+            assert_eq!(planar.position, body.position[..2]);
+            assert_eq!(planar.velocity, body.velocity[..2]);
+            assert_eq!(hyper.position[..3], body.position[..]);
+            assert_eq!(hyper.position[3], body.position[0]);
+            assert_eq!(hyper.velocity[3], body.velocity[0]);
+        }
+
+        let system = parse_input("<x=0>\n<x=2>").unwrap();
+        assert_eq!(system.period(false).unwrap(), 6);
+        assert!(parse_input("<x=0, y=1>\n<x=2>").is_err());
+        assert!(parse_input("<x=0, y=1>\n<y=2, x=0>").is_err());
+        assert!(parse_input("").is_err());
+    }
 }
@@ -1,8 +1,8 @@
 extern crate intcode_vm;
 
+use aoc_runner::Day;
 use intcode_vm::*;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -11,15 +11,13 @@ macro_rules! err {
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    part_1(&input)?;
-    part_2(&input)?;
-    Ok(())
+    Day::new(|input| Ok(input.to_string()))
+        .part(|input| part_1(input))
+        .part(|input| part_2(input))
+        .run()
 }
 
-fn part_1(input: &str) -> Result<()> {
+fn part_1(input: &str) -> Result<isize> {
     let mut vm = IntCodeVm::new(input)?;
     let mut vm_input = None;
     let mut block_count = 0;
@@ -43,11 +41,10 @@ fn part_1(input: &str) -> Result<()> {
         }
     }
 
-    writeln!(io::stdout(), "Part 1 : {}", block_count)?;
-    Ok(())
+    Ok(block_count)
 }
 
-fn part_2(input: &str) -> Result<()> {
+fn part_2(input: &str) -> Result<isize> {
     let mut vm = IntCodeVm::new(input)?;
     let mut vm_input = None;
     let mut current_block: Vec<isize> = vec![];
@@ -88,10 +85,10 @@ fn part_2(input: &str) -> Result<()> {
         }
     }
 
-    writeln!(io::stdout(), "Part 2 : {}", current_score)?;
-    Ok(())
+    Ok(current_score)
 }
 
+#[allow(dead_code, clippy::enum_variant_names)]
 enum Block {
     Empty(isize, isize),
     Wall(isize, isize),
@@ -1,25 +1,18 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 3 tests
-    test bench::bench_parse_input ... bench:   1,483,992 ns/iter (+/- 91,046)
-    test bench::bench_part_1      ... bench:      40,603 ns/iter (+/- 1,074)
-    test bench::bench_part_2      ... bench:      67,068 ns/iter (+/- 2,124)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Report lines failing each policy : cargo run --release -- --report [--min-length <length>] [--require <class,...>] [--require-any <class,...>] [--forbid <class,...>] < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
+use aoc_runner::{Day, Flag, Options};
 use std::convert::TryFrom;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -35,22 +28,187 @@ struct PasswordRules {
     password: String,
 }
 
-impl PasswordRules {
-    fn is_password_valid_1(&self) -> bool {
-        let occurrences = self
+// Policies only see a single line of the database. Their Display is the name used in reports
+trait PasswordPolicy: fmt::Display {
+    fn is_valid(&self, rules: &PasswordRules) -> bool;
+}
+
+// The character appears between the first and the second number of times
+struct OccurrenceRange;
+
+impl PasswordPolicy for OccurrenceRange {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        let occurrences = rules
             .password
             .chars()
-            .filter(|&v| v == self.character)
+            .filter(|&v| v == rules.character)
             .count();
-        self.first_number <= occurrences && occurrences <= self.second_number
+        rules.first_number <= occurrences && occurrences <= rules.second_number
+    }
+}
+
+impl fmt::Display for OccurrenceRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "occurrence range")
+    }
+}
+
+// The character appears at exactly one of both positions, the first character being at position 1
+struct PositionalXor;
+
+impl PasswordPolicy for PositionalXor {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        let char_at = |position: usize| {
+            position
+                .checked_sub(1)
+                .and_then(|i| rules.password.chars().nth(i))
+        };
+
+        (char_at(rules.first_number) == Some(rules.character))
+            != (char_at(rules.second_number) == Some(rules.character))
     }
+}
+
+impl fmt::Display for PositionalXor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "positional xor")
+    }
+}
+
+struct All(Vec<Box<dyn PasswordPolicy>>);
+
+impl PasswordPolicy for All {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        self.0.iter().all(|policy| policy.is_valid(rules))
+    }
+}
+
+impl fmt::Display for All {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, "all", &self.0)
+    }
+}
 
-    fn is_password_valid_2(&self) -> bool {
-        let first_char = self.password.chars().nth(self.first_number - 1);
-        let second_char = self.password.chars().nth(self.second_number - 1);
+struct Any(Vec<Box<dyn PasswordPolicy>>);
 
-        first_char != second_char
-            && (first_char == Some(self.character) || second_char == Some(self.character))
+impl PasswordPolicy for Any {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        self.0.iter().any(|policy| policy.is_valid(rules))
+    }
+}
+
+impl fmt::Display for Any {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, "any", &self.0)
+    }
+}
+
+fn write_list(
+    f: &mut fmt::Formatter,
+    name: &str,
+    policies: &[Box<dyn PasswordPolicy>],
+) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    for (i, policy) in policies.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", policy)?;
+    }
+    write!(f, ")")
+}
+
+struct Not(Box<dyn PasswordPolicy>);
+
+impl PasswordPolicy for Not {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        !self.0.is_valid(rules)
+    }
+}
+
+impl fmt::Display for Not {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "not({})", self.0)
+    }
+}
+
+// Length in characters, not in bytes
+struct MinLength(usize);
+
+impl PasswordPolicy for MinLength {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        rules.password.chars().count() >= self.0
+    }
+}
+
+impl fmt::Display for MinLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "min length {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharacterClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    // Neither alphanumeric nor whitespace
+    Symbol,
+    NonAscii,
+}
+
+impl CharacterClass {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            CharacterClass::Lowercase => c.is_lowercase(),
+            CharacterClass::Uppercase => c.is_uppercase(),
+            CharacterClass::Digit => c.is_numeric(),
+            CharacterClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+            CharacterClass::NonAscii => !c.is_ascii(),
+        }
+    }
+}
+
+impl FromStr for CharacterClass {
+    type Err = Box<dyn Error>;
+
+    fn from_str(class: &str) -> Result<Self> {
+        match class {
+            "lowercase" => Ok(CharacterClass::Lowercase),
+            "uppercase" => Ok(CharacterClass::Uppercase),
+            "digit" => Ok(CharacterClass::Digit),
+            "symbol" => Ok(CharacterClass::Symbol),
+            "non-ascii" => Ok(CharacterClass::NonAscii),
+            _ => err!("Unknown character class : {}", class),
+        }
+    }
+}
+
+impl fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CharacterClass::Lowercase => "lowercase",
+            CharacterClass::Uppercase => "uppercase",
+            CharacterClass::Digit => "digit",
+            CharacterClass::Symbol => "symbol",
+            CharacterClass::NonAscii => "non-ascii",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// At least one character of the class
+struct Contains(CharacterClass);
+
+impl PasswordPolicy for Contains {
+    fn is_valid(&self, rules: &PasswordRules) -> bool {
+        rules.password.chars().any(|c| self.0.contains(c))
+    }
+}
+
+impl fmt::Display for Contains {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "contains {}", self.0)
     }
 }
 
@@ -62,7 +220,7 @@ impl TryFrom<&str> for PasswordRules {
 
         lazy_static! {
             static ref DAY_02_PASSWORD_RULE_REGEX: Regex = Regex::new(
-                r"^(?P<first_number>\d+)-(?P<second_number>\d+) (?P<character>[a-z]): (?P<password>[a-z]+)$"
+                r"^(?P<first_number>\d+)-(?P<second_number>\d+) (?P<character>\S): (?P<password>\S+)$"
             )
             .expect("Invalid DAY_02_PASSWORD_RULE_REGEX!");
         }
@@ -91,92 +249,133 @@ impl TryFrom<&str> for PasswordRules {
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[
+        Flag::Switch("--report"),
+        Flag::Value("--min-length"),
+        Flag::Value("--require"),
+        Flag::Value("--require-any"),
+        Flag::Value("--forbid"),
+    ])?;
+    if options.has("--report") {
+        let mut policies: Vec<Box<dyn PasswordPolicy>> =
+            vec![Box::new(OccurrenceRange), Box::new(PositionalXor)];
+        if let Some(length) = options.parse_value::<usize>("--min-length")? {
+            policies.push(Box::new(MinLength(length)));
+        }
+        let contains = |flag: &str| -> Result<Vec<Box<dyn PasswordPolicy>>> {
+            let mut policies: Vec<Box<dyn PasswordPolicy>> = vec![];
+            if let Some(classes) = options.value(flag) {
+                for class in classes.split(',') {
+                    policies.push(Box::new(Contains(CharacterClass::from_str(class)?)));
+                }
+            }
+            Ok(policies)
+        };
+        let required = contains("--require")?;
+        if !required.is_empty() {
+            policies.push(Box::new(All(required)));
+        }
+        let required_any = contains("--require-any")?;
+        if !required_any.is_empty() {
+            policies.push(Box::new(Any(required_any)));
+        }
+        for forbidden in contains("--forbid")? {
+            policies.push(Box::new(Not(forbidden)));
+        }
+        return report(&options, &policies);
+    }
 
-    let password_rules = parse_input(&input)?;
+    day().run_with(&options)
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&password_rules))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&password_rules))?;
-    Ok(())
+fn day() -> Day<Vec<PasswordRules>> {
+    Day::new(parse_input)
+        .part(|password_rules| Ok(count_valid(password_rules, &OccurrenceRange)))
+        .part(|password_rules| Ok(count_valid(password_rules, &PositionalXor)))
 }
 
 fn parse_input(input: &str) -> Result<Vec<PasswordRules>> {
     input.lines().map(PasswordRules::try_from).collect()
 }
 
-fn part_1(password_rules: &[PasswordRules]) -> usize {
-    password_rules
-        .iter()
-        .filter(|v| v.is_password_valid_1())
-        .count()
+fn report(options: &Options, policies: &[Box<dyn PasswordPolicy>]) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let lines: Vec<&str> = input.lines().collect();
+    let password_rules = parse_input(&input)?;
+
+    let mut stdout = io::stdout();
+    for (i, rules) in password_rules.iter().enumerate() {
+        let failed: Vec<String> = policies
+            .iter()
+            .filter(|policy| !policy.is_valid(rules))
+            .map(|policy| policy.to_string())
+            .collect();
+        if !failed.is_empty() {
+            writeln!(
+                stdout,
+                "Line {} ({}) fails : {}",
+                i + 1,
+                lines[i],
+                failed.join(", ")
+            )?;
+        }
+    }
+    for policy in policies {
+        writeln!(
+            stdout,
+            "{} : {} valid out of {}",
+            policy,
+            count_valid(&password_rules, policy.as_ref()),
+            password_rules.len()
+        )?;
+    }
+    Ok(())
 }
 
-fn part_2(password_rules: &[PasswordRules]) -> usize {
-    password_rules
-        .iter()
-        .filter(|v| v.is_password_valid_2())
-        .count()
+fn count_valid(password_rules: &[PasswordRules], policy: &dyn PasswordPolicy) -> usize {
+    password_rules.iter().filter(|v| policy.is_valid(v)).count()
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let password_rules = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&password_rules), 2);
+    fn test_composed_policies() -> Result<()> {
+        let rules = parse_input("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc")?;
+        let policy = Any(vec![
+            Box::new(All(vec![Box::new(OccurrenceRange), Box::new(MinLength(6))])),
+            Box::new(Not(Box::new(PositionalXor))),
+        ]);
+        assert_eq!(
+            policy.to_string(),
+            "any(all(occurrence range, min length 6), not(positional xor))"
+        );
+        assert_eq!(count_valid(&rules, &policy), 2);
+        assert!(policy.is_valid(&rules[2]));
+        assert!(!policy.is_valid(&rules[0]));
         Ok(())
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let password_rules = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(&password_rules), 1);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let password_rules = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&password_rules)));
-        Ok(())
-    }
+    fn test_unicode() -> Result<()> {
+        let rules = PasswordRules::try_from("2-3 é: aéÉé9")?;
+        assert!(OccurrenceRange.is_valid(&rules));
+        assert!(PositionalXor.is_valid(&rules));
+        assert!(MinLength(5).is_valid(&rules));
+        assert!(!MinLength(6).is_valid(&rules));
+        for class in &["lowercase", "uppercase", "digit", "non-ascii"] {
+            assert!(Contains(CharacterClass::from_str(class)?).is_valid(&rules));
+        }
+        assert!(!Contains(CharacterClass::Symbol).is_valid(&rules));
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let password_rules = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&password_rules)));
+        // Positions start at 1, position 0 never holds the character
+        let rules = PasswordRules::try_from("0-1 a: ab")?;
+        assert!(PositionalXor.is_valid(&rules));
+        assert!(PasswordRules::try_from("1-2 a: a b").is_err());
         Ok(())
     }
 }
@@ -1,49 +1,33 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:      67,188 ns/iter (+/- 11,135)
-    test bench::bench_part_1      ... bench:     540,429 ns/iter (+/- 29,689)
-    test bench::bench_part_2      ... bench:   3,979,029 ns/iter (+/- 1,322,497)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
 
+use aoc_runner::Day;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
+// Line length, number of lines and position of every tree
+type Forest = (usize, usize, Vec<(usize, usize)>);
 
 macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    let (line_length, max_line, tree_positions) = parse_input(&input)?;
+    day().run()
+}
 
-    writeln!(
-        io::stdout(),
-        "Part 1 : {}",
-        part_1(line_length, max_line, &tree_positions)
-    )?;
-    writeln!(
-        io::stdout(),
-        "Part 2 : {}",
-        part_2(line_length, max_line, &tree_positions)
-    )?;
-    Ok(())
+fn day() -> Day<Forest> {
+    Day::new(parse_input)
+        .part(|(line_length, max_line, tree_positions)| {
+            Ok(part_1(*line_length, *max_line, tree_positions))
+        })
+        .part(|(line_length, max_line, tree_positions)| {
+            Ok(part_2(*line_length, *max_line, tree_positions))
+        })
 }
 
-fn parse_input(input: &str) -> Result<(usize, usize, Vec<(usize, usize)>)> {
+fn parse_input(input: &str) -> Result<Forest> {
     let mut tree_positions = vec![];
     let mut line_length = None;
     let mut max_line = 0;
@@ -111,63 +95,4 @@ fn traverse_trees_slope(
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let (line_length, max_line, tree_positions) = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(line_length, max_line, &tree_positions), 7);
-        Ok(())
-    }
-
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let (line_length, max_line, tree_positions) = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(line_length, max_line, &tree_positions), 336);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let (line_length, max_line, tree_positions) = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(line_length, max_line, &tree_positions)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let (line_length, max_line, tree_positions) = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(line_length, max_line, &tree_positions)));
-        Ok(())
-    }
-}
+aoc_runner::examples!(day(), example_1);
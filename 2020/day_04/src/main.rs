@@ -1,27 +1,21 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:   1,099,234 ns/iter (+/- 63,458)
-    test bench::bench_part_1      ... bench:       1,571 ns/iter (+/- 83)
-    test bench::bench_part_2      ... bench:     250,787 ns/iter (+/- 31,757)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Check a custom schema : cargo run --release -- --schema <file> < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
+use aoc_runner::{Day, Flag, Options};
 use regex::Regex;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -29,160 +23,298 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Default)]
-struct Passport {
-    byr: Option<String>,
-    iyr: Option<String>,
-    eyr: Option<String>,
-    hgt: Option<String>,
-    hcl: Option<String>,
-    ecl: Option<String>,
-    pid: Option<String>,
-    cid: Option<String>,
+const PART_1_SCHEMA: &str = include_str!("../schemas/part_1.schema");
+const PART_2_SCHEMA: &str = include_str!("../schemas/part_2.schema");
+
+// Built-in schemas are only parsed, and their regexes compiled, once
+lazy_static! {
+    static ref DAY_04_PART_1_SCHEMA: Schema =
+        Schema::from_str(PART_1_SCHEMA).expect("Invalid DAY_04_PART_1_SCHEMA!");
+    static ref DAY_04_PART_2_SCHEMA: Schema =
+        Schema::from_str(PART_2_SCHEMA).expect("Invalid DAY_04_PART_2_SCHEMA!");
 }
 
-impl Passport {
-    fn is_valid(&self) -> bool {
-        self.byr.is_some()
-            && self.iyr.is_some()
-            && self.eyr.is_some()
-            && self.hgt.is_some()
-            && self.hcl.is_some()
-            && self.ecl.is_some()
-            && self.pid.is_some()
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Violation {
+    Missing,
+    Unknown,
+    Duplicated,
+    NotANumber(String),
+    OutOfRange(u64, RangeInclusive<u64>),
+    UnknownUnit(String),
+    NoMatch(String),
+    NotInEnum(String),
+}
 
-    fn is_valid_2(&self) -> bool {
-        lazy_static! {
-            static ref DAY_04_HEIGHT_REGEX: Regex =
-                Regex::new(r"^(?P<height>\d{2,3})(?P<measurement>(cm)|(in))$")
-                    .expect("Invalid DAY_04_HEIGHT_REGEX!");
-            static ref DAY_04_COLOR_REGEX: Regex =
-                Regex::new(r"^(?P<color>#[0-9a-f]{6})$").expect("Invalid DAY_04_COLOR_REGEX!");
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Missing => write!(f, "missing"),
+            Violation::Unknown => write!(f, "unknown field"),
+            Violation::Duplicated => write!(f, "defined several times"),
+            Violation::NotANumber(value) => write!(f, "{} is not a number", value),
+            Violation::OutOfRange(number, range) => write!(
+                f,
+                "{} is out of range {}..{}",
+                number,
+                range.start(),
+                range.end()
+            ),
+            Violation::UnknownUnit(value) => write!(f, "{} has an unknown unit", value),
+            Violation::NoMatch(value) => write!(f, "{} doesn't match the pattern", value),
+            Violation::NotInEnum(value) => write!(f, "{} is not an accepted value", value),
         }
-        if let Some(ref byr) = self.byr {
-            if let Ok(year) = byr.parse::<usize>() {
-                if year < 1920 || year > 2002 {
-                    return false;
+    }
+}
+
+#[derive(Debug)]
+enum Rule {
+    Any,
+    // Four digits year
+    Year(RangeInclusive<u64>),
+    // Accepted range for every unit, the unit being possibly empty
+    Number(Vec<(RangeInclusive<u64>, String)>),
+    Regex(Regex),
+    Enum(Vec<String>),
+}
+
+impl Rule {
+    fn check(&self, value: &str) -> Option<Violation> {
+        let in_range = |number: &str, range: &RangeInclusive<u64>| match number.parse::<u64>() {
+            Ok(number) if range.contains(&number) => None,
+            Ok(number) => Some(Violation::OutOfRange(number, range.clone())),
+            Err(_) => Some(Violation::NotANumber(value.to_string())),
+        };
+
+        match self {
+            Rule::Any => None,
+            Rule::Year(range) => {
+                if value.len() != 4 || !value.chars().all(|c| c.is_ascii_digit()) {
+                    return Some(Violation::NotANumber(value.to_string()));
                 }
-            } else {
-                return false;
+                in_range(value, range)
             }
-        } else {
-            return false;
-        }
-        if let Some(ref iyr) = self.iyr {
-            if let Ok(year) = iyr.parse::<usize>() {
-                if year < 2010 || year > 2020 {
-                    return false;
+            Rule::Number(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                if number.is_empty() {
+                    return Some(Violation::NotANumber(value.to_string()));
+                }
+                match units.iter().find(|(_, accepted)| accepted == unit) {
+                    Some((range, _)) => in_range(number, range),
+                    None => Some(Violation::UnknownUnit(value.to_string())),
                 }
-            } else {
-                return false;
             }
-        } else {
-            return false;
-        }
-        if let Some(ref eyr) = self.eyr {
-            if let Ok(year) = eyr.parse::<usize>() {
-                if year < 2020 || year > 2030 {
-                    return false;
+            Rule::Regex(regex) => {
+                if regex.is_match(value) {
+                    None
+                } else {
+                    Some(Violation::NoMatch(value.to_string()))
+                }
+            }
+            Rule::Enum(accepted) => {
+                if accepted.iter().any(|accepted| accepted == value) {
+                    None
+                } else {
+                    Some(Violation::NotInEnum(value.to_string()))
                 }
-            } else {
-                return false;
             }
-        } else {
-            return false;
         }
-        if let Some(ref hgt) = self.hgt {
-            if let Some(cap) = DAY_04_HEIGHT_REGEX.captures(&hgt) {
-                let height = cap["height"].parse::<usize>().unwrap(); // Safe unwrap ensured by the regex
-                match &cap["measurement"] {
-                    "cm" => {
-                        if height < 150 || height > 193 {
-                            return false;
-                        }
-                    }
-                    "in" => {
-                        if height < 59 || height > 76 {
-                            return false;
-                        }
-                    }
-                    _ => unreachable!("DAY_04_HEIGHT_REGEX should have taken care of all cases"),
+    }
+}
+
+// `year 1920..2002`, `number 150..193cm 59..76in`, `regex ^#[0-9a-f]{6}$` or `enum amb blu`
+impl FromStr for Rule {
+    type Err = Box<dyn Error>;
+
+    fn from_str(rule: &str) -> Result<Self> {
+        fn parse_range(range: &str) -> Result<RangeInclusive<u64>> {
+            match range.split_once("..") {
+                Some((min, max)) => Ok(min.parse()?..=max.parse()?),
+                None => err!("Invalid range : {}", range),
+            }
+        }
+
+        let (kind, arguments) = rule.split_once(' ').unwrap_or((rule, ""));
+        let arguments = arguments.trim();
+        if kind.is_empty() {
+            return Ok(Rule::Any);
+        }
+        if arguments.is_empty() {
+            err!("Missing arguments for rule : {}", rule)
+        }
+
+        match kind {
+            "year" => Ok(Rule::Year(parse_range(arguments)?)),
+            "number" => {
+                let mut units = vec![];
+                for range in arguments.split_whitespace() {
+                    let split = range
+                        .rfind(|c: char| c.is_ascii_digit())
+                        .map_or(0, |i| i + 1);
+                    let (range, unit) = range.split_at(split);
+                    units.push((parse_range(range)?, unit.to_string()));
                 }
-            } else {
-                return false;
+                Ok(Rule::Number(units))
             }
-        } else {
-            return false;
+            "regex" => Ok(Rule::Regex(Regex::new(arguments)?)),
+            "enum" => Ok(Rule::Enum(
+                arguments.split_whitespace().map(String::from).collect(),
+            )),
+            _ => err!("Unknown rule : {}", kind),
         }
-        if let Some(ref hcl) = self.hcl {
-            if DAY_04_COLOR_REGEX.captures(&hcl).is_none() {
-                return false;
+    }
+}
+
+#[derive(Debug)]
+struct FieldSchema {
+    required: bool,
+    rule: Rule,
+}
+
+#[derive(Debug)]
+struct Schema {
+    fields: BTreeMap<String, FieldSchema>,
+}
+
+impl Schema {
+    // Violations of every invalid field, a passport being valid when there are none
+    fn validate<'a>(&'a self, passport: &'a Passport) -> BTreeMap<&'a str, Vec<Violation>> {
+        let mut violations: BTreeMap<&str, Vec<Violation>> = BTreeMap::new();
+
+        for (name, field) in &self.fields {
+            if field.required && passport.values(name).next().is_none() {
+                violations.entry(name).or_default().push(Violation::Missing);
             }
-        } else {
-            return false;
         }
-        if let Some(ref ecl) = self.ecl {
-            match ecl.as_str() {
-                "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => {}
-                _ => return false,
+        for (i, (name, value)) in passport.fields.iter().enumerate() {
+            let mut field_violations = vec![];
+            if passport.fields[..i].iter().any(|(other, _)| other == name) {
+                field_violations.push(Violation::Duplicated);
             }
-        } else {
-            return false;
+            match self.fields.get(name) {
+                Some(field) => field_violations.extend(field.rule.check(value)),
+                None => field_violations.push(Violation::Unknown),
+            }
+            if !field_violations.is_empty() {
+                violations.entry(name).or_default().extend(field_violations);
+            }
+        }
+        violations
+    }
+
+    fn is_valid(&self, passport: &Passport) -> bool {
+        self.validate(passport).is_empty()
+    }
+}
+
+// One field per line : `<field> = <required|optional> [rule]`, `#` starting a comment line
+impl FromStr for Schema {
+    type Err = Box<dyn Error>;
+
+    fn from_str(input: &str) -> Result<Self> {
+        lazy_static! {
+            static ref DAY_04_SCHEMA_FIELD_REGEX: Regex =
+                Regex::new(r"^(?P<name>[^=\s]+)\s*=\s*(?P<presence>\S+)(?:\s+(?P<rule>.*))?$")
+                    .expect("Invalid DAY_04_SCHEMA_FIELD_REGEX!");
         }
-        if let Some(ref pid) = self.pid {
-            if pid.len() != 9 || pid.parse::<usize>().is_err() {
-                return false;
+
+        let mut fields = BTreeMap::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let cap = match DAY_04_SCHEMA_FIELD_REGEX.captures(line) {
+                Some(cap) => cap,
+                None => err!("Invalid schema field at line {} : {}", i + 1, line),
+            };
+            let name = &cap["name"];
+            let rule = cap.name("rule").map_or("", |rule| rule.as_str());
+            let required = match &cap["presence"] {
+                "required" => true,
+                "optional" => false,
+                _ => err!(
+                    "Field should be required or optional at line {} : {}",
+                    i + 1,
+                    &cap["presence"]
+                ),
+            };
+            let rule = match Rule::from_str(rule.trim()) {
+                Ok(rule) => rule,
+                Err(e) => err!("Invalid rule at line {} : {}", i + 1, e),
+            };
+
+            if fields
+                .insert(name.to_string(), FieldSchema { required, rule })
+                .is_some()
+            {
+                err!("Field {} is defined twice in the schema", name)
             }
-        } else {
-            return false;
         }
 
-        true
+        Ok(Schema { fields })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Passport {
+    // Input line where the passport starts
+    line: usize,
+    fields: Vec<(String, String)>,
+}
+
+impl Passport {
+    fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
     }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[Flag::Value("--schema")])?;
+    if let Some(file) = options.value("--schema") {
+        return report(&options, &Schema::from_str(&fs::read_to_string(file)?)?);
+    }
 
-    let passports = parse_input(&input)?;
+    day().run_with(&options)
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&passports))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&passports))?;
-    Ok(())
+fn day() -> Day<Vec<Passport>> {
+    Day::new(parse_input)
+        .part(|passports| Ok(part_1(passports)))
+        .part(|passports| Ok(part_2(passports)))
 }
 
 fn parse_input(input: &str) -> Result<Vec<Passport>> {
     let mut passports = vec![];
-
     let mut current_passport = Passport {
+        line: 1,
         ..Default::default()
     };
 
-    for line in input.lines() {
+    for (i, line) in input.lines().enumerate() {
         if line.is_empty() {
             passports.push(current_passport);
             current_passport = Passport {
+                line: i + 2,
                 ..Default::default()
             };
         } else {
             for split_line in line.split(' ') {
-                let key_value: Vec<&str> = split_line.split(':').collect();
-                if key_value.len() == 2 {
-                    match key_value[0] {
-                        "byr" => current_passport.byr = Some(key_value[1].into()),
-                        "iyr" => current_passport.iyr = Some(key_value[1].into()),
-                        "eyr" => current_passport.eyr = Some(key_value[1].into()),
-                        "hgt" => current_passport.hgt = Some(key_value[1].into()),
-                        "hcl" => current_passport.hcl = Some(key_value[1].into()),
-                        "ecl" => current_passport.ecl = Some(key_value[1].into()),
-                        "pid" => current_passport.pid = Some(key_value[1].into()),
-                        "cid" => current_passport.cid = Some(key_value[1].into()),
-                        _ => err!("Invalid passport key found : {}", split_line),
+                match split_line.split_once(':') {
+                    Some((key, value)) if !key.is_empty() && !value.contains(':') => {
+                        current_passport
+                            .fields
+                            .push((key.to_string(), value.to_string()))
                     }
-                } else {
-                    err!("Invalid input : {}", split_line)
+                    _ => err!("Invalid input at line {} : {}", i + 1, split_line),
                 }
             }
         }
@@ -193,78 +325,118 @@ fn parse_input(input: &str) -> Result<Vec<Passport>> {
     Ok(passports)
 }
 
+fn report(options: &Options, schema: &Schema) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let passports = parse_input(&input)?;
+
+    let mut stdout = io::stdout();
+    let mut valid = 0;
+    for (i, passport) in passports.iter().enumerate() {
+        let violations = schema.validate(passport);
+        if violations.is_empty() {
+            valid += 1;
+            continue;
+        }
+
+        writeln!(stdout, "Passport {} (line {}) :", i + 1, passport.line)?;
+        for (field, field_violations) in violations {
+            for violation in field_violations {
+                writeln!(stdout, "    {} : {}", field, violation)?;
+            }
+        }
+    }
+    writeln!(stdout, "Valid passports : {} / {}", valid, passports.len())?;
+    Ok(())
+}
+
+fn count_valid(passports: &[Passport], schema: &Schema) -> usize {
+    passports
+        .iter()
+        .filter(|passport| schema.is_valid(passport))
+        .count()
+}
+
 fn part_1(passports: &[Passport]) -> usize {
-    passports.iter().filter(|v| v.is_valid()).count()
+    count_valid(passports, &DAY_04_PART_1_SCHEMA)
 }
 
 fn part_2(passports: &[Passport]) -> usize {
-    passports.iter().filter(|v| v.is_valid_2()).count()
+    count_valid(passports, &DAY_04_PART_2_SCHEMA)
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    fn read_test_file_2() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test2.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let passports = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&passports), 2);
+    fn test_rules() -> Result<()> {
+        let schema = &*DAY_04_PART_2_SCHEMA;
+        let check = |field: &str, value: &str| schema.fields[field].rule.check(value);
+
+        assert_eq!(check("byr", "2002"), None);
+        assert_eq!(
+            check("byr", "2003"),
+            Some(Violation::OutOfRange(2003, 1920..=2002))
+        );
+        assert_eq!(check("hgt", "60in"), None);
+        assert_eq!(check("hgt", "190cm"), None);
+        assert_eq!(
+            check("hgt", "190in"),
+            Some(Violation::OutOfRange(190, 59..=76))
+        );
+        assert_eq!(
+            check("hgt", "190"),
+            Some(Violation::UnknownUnit("190".to_string()))
+        );
+        assert_eq!(check("hcl", "#123abc"), None);
+        assert!(check("hcl", "#123abz").is_some());
+        assert!(check("hcl", "123abc").is_some());
+        assert_eq!(check("ecl", "brn"), None);
+        assert_eq!(
+            check("ecl", "wat"),
+            Some(Violation::NotInEnum("wat".to_string()))
+        );
+        assert_eq!(check("pid", "000000001"), None);
+        assert!(check("pid", "0123456789").is_some());
         Ok(())
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let passports = parse_input(&read_test_file_2()?)?;
-        assert_eq!(part_2(&passports), 4);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
+    fn test_violations() -> Result<()> {
+        let schema = Schema::from_str("byr = required year 1920..2002\ncid = optional")?;
+        let passports = parse_input("byr:1919 foo:bar\nbyr:19x0\n\ncid:1")?;
+        assert_eq!(passports[1].line, 4);
+
+        let violations = schema.validate(&passports[0]);
+        assert_eq!(
+            violations["byr"],
+            vec![
+                Violation::OutOfRange(1919, 1920..=2002),
+                Violation::Duplicated,
+                Violation::NotANumber("19x0".to_string())
+            ]
+        );
+        assert_eq!(violations["foo"], vec![Violation::Unknown]);
+        assert_eq!(
+            schema
+                .validate(&passports[1])
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![("byr", vec![Violation::Missing])]
+        );
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let passports = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&passports)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let passports = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&passports)));
-        Ok(())
+    #[test]
+    fn test_invalid_schemas() {
+        assert!(Schema::from_str("byr required").is_err());
+        assert!(Schema::from_str("byr = sometimes").is_err());
+        assert!(Schema::from_str("byr = required year 1920").is_err());
+        assert!(Schema::from_str("byr = required color red").is_err());
+        assert!(Schema::from_str("hcl = required regex (").is_err());
+        assert!(Schema::from_str("byr = optional\nbyr = required").is_err());
     }
 }
@@ -1,22 +1,13 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 4 tests
-    test tests::test_part_1 ... ignored
-    test bench::bench_parse_input ... bench:      98,888 ns/iter (+/- 9,621)
-    test bench::bench_part_1      ... bench:         681 ns/iter (+/- 83)
-    test bench::bench_part_2      ... bench:       3,128 ns/iter (+/- 224)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the plane geometry : cargo run --release -- --row-bits 7 --column-bits 3 < input/input.txt
+// List every free seat : cargo run --release -- --free < input/input.txt
 
-use std::convert::TryFrom;
+use aoc_runner::{Day, Flag, Options};
+use std::collections::HashSet;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -24,144 +15,222 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 struct Seat {
-    row: usize,
-    column: usize,
+    row: u64,
+    column: u64,
 }
 
-impl Seat {
-    fn get_seat_id(&self) -> usize {
-        self.row * 8 + self.column
-    }
+// Binary space partitioning : every character of a pass halves the remaining rows (F/B) or
+// columns (L/R), keeping the lower (F, L) or the upper (B, R) half. The pass is therefore the
+// row then the column written in binary, most significant bit first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Codec {
+    row_bits: u32,
+    column_bits: u32,
 }
 
-impl TryFrom<&str> for Seat {
-    type Error = Box<dyn Error>;
-
-    fn try_from(value: &str) -> Result<Self> {
-        if value.len() != 10 {
-            err!("Input line should have a length of 10 chars")
-        } else {
-            let mut max_row = 127;
-            let mut min_row = 0;
-            let mut max_column = 7;
-            let mut min_column = 0;
-
-            for r in value[..7].chars() {
-                match r {
-                    'F' => max_row = (max_row + min_row - 1) / 2,
-                    'B' => min_row = (max_row + min_row + 1) / 2,
-                    _ => err!("Invalid character found while determining row : {}", r),
-                }
-            }
+impl Codec {
+    fn new(row_bits: u32, column_bits: u32) -> Result<Self> {
+        if row_bits + column_bits > 63 {
+            err!("Passes can't be longer than 63 characters")
+        }
+        Ok(Codec {
+            row_bits,
+            column_bits,
+        })
+    }
 
-            for c in value[7..].chars() {
+    fn decode(&self, pass: &str) -> Result<Seat> {
+        fn bits(half: &str, lower: char, upper: char) -> Result<u64> {
+            let mut value = 0;
+            for c in half.chars() {
+                value <<= 1;
                 match c {
-                    'L' => max_column = (max_column + min_column - 1) / 2,
-                    'R' => min_column = (max_column + min_column + 1) / 2,
-                    _ => err!("Invalid character found while determining column : {}", c),
+                    c if c == lower => {}
+                    c if c == upper => value |= 1,
+                    _ => err!("Invalid character {} : expected {} or {}", c, lower, upper),
                 }
             }
+            Ok(value)
+        }
 
-            Ok(Seat {
-                row: max_row,
-                column: max_column,
-            })
+        let len = (self.row_bits + self.column_bits) as usize;
+        if pass.len() != len || !pass.is_ascii() {
+            err!("Pass should have a length of {} chars : {}", len, pass)
         }
+        let (row, column) = pass.split_at(self.row_bits as usize);
+        Ok(Seat {
+            row: bits(row, 'F', 'B')?,
+            column: bits(column, 'L', 'R')?,
+        })
     }
-}
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    fn encode(&self, seat: &Seat) -> Result<String> {
+        fn chars(value: u64, bits: u32, lower: char, upper: char) -> impl Iterator<Item = char> {
+            (0..bits)
+                .rev()
+                .map(move |bit| if value >> bit & 1 == 1 { upper } else { lower })
+        }
 
-    let mut seats = parse_input(&input)?;
+        if seat.row >> self.row_bits != 0 || seat.column >> self.column_bits != 0 {
+            err!("Seat {:?} doesn't fit in the plane", seat)
+        }
+        Ok(chars(seat.row, self.row_bits, 'F', 'B')
+            .chain(chars(seat.column, self.column_bits, 'L', 'R'))
+            .collect())
+    }
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&seats)?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&mut seats)?)?;
-    Ok(())
+    fn seat_id(&self, seat: &Seat) -> u64 {
+        seat.row << self.column_bits | seat.column
+    }
+
+    fn seat(&self, seat_id: u64) -> Seat {
+        Seat {
+            row: seat_id >> self.column_bits,
+            column: seat_id & ((1 << self.column_bits) - 1),
+        }
+    }
 }
 
-fn parse_input(input: &str) -> Result<Vec<Seat>> {
-    input.lines().map(Seat::try_from).collect()
+struct BoardingPasses {
+    codec: Codec,
+    seats: Vec<Seat>,
 }
 
-fn part_1(seats: &[Seat]) -> Result<usize> {
-    match seats.iter().map(|v| v.get_seat_id()).max() {
-        Some(max) => Ok(max),
-        None => err!("Input is empty!"),
+impl BoardingPasses {
+    fn seat_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.seats.iter().map(move |seat| self.codec.seat_id(seat))
     }
-}
 
-fn part_2(seats: &mut Vec<Seat>) -> Result<usize> {
-    seats.sort();
-    let mut current_seat_id = 0;
-
-    for (i, seat) in seats.iter().enumerate() {
-        if i == 0 {
-            current_seat_id = seat.get_seat_id();
-        } else {
-            current_seat_id += 1;
-            if current_seat_id != seat.get_seat_id() {
-                return Ok(seat.get_seat_id() - 1);
-            }
-        }
+    // Seats without a pass whose both neighbors (seat ids -1 and +1) have one, in increasing order
+    fn free_seats(&self) -> Vec<u64> {
+        let occupied: HashSet<u64> = self.seat_ids().collect();
+        let mut free: Vec<u64> = occupied
+            .iter()
+            .filter_map(|&id| id.checked_add(1))
+            .filter(|id| !occupied.contains(id) && occupied.contains(&(id + 1)))
+            .collect();
+        free.sort_unstable();
+        free
     }
-    err!("Couldn't find santa's seat!")
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+fn main() -> Result<()> {
+    let options = Options::from_env(&[
+        Flag::Value("--row-bits"),
+        Flag::Value("--column-bits"),
+        Flag::Switch("--free"),
+    ])?;
+    let codec = Codec::new(
+        options.parse_value("--row-bits")?.unwrap_or(7),
+        options.parse_value("--column-bits")?.unwrap_or(3),
+    )?;
+    if options.has("--free") {
+        return list_free_seats(&options, codec);
     }
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let seats = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&seats)?, 820);
-        Ok(())
-    }
+    day(codec).run_with(&options)
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+fn day(codec: Codec) -> Day<BoardingPasses> {
+    Day::new(move |input| parse_input(input, codec))
+        .part(part_1)
+        .part(part_2)
+}
 
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
+fn parse_input(input: &str, codec: Codec) -> Result<BoardingPasses> {
+    Ok(BoardingPasses {
+        codec,
+        seats: input
+            .lines()
+            .map(|pass| codec.decode(pass))
+            .collect::<Result<Vec<Seat>>>()?,
+    })
+}
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+fn list_free_seats(options: &Options, codec: Codec) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let passes = parse_input(&input, codec)?;
+
+    let mut stdout = io::stdout();
+    for seat_id in passes.free_seats() {
+        let seat = codec.seat(seat_id);
+        writeln!(
+            stdout,
+            "Seat {} : row {}, column {}, pass {}",
+            seat_id,
+            seat.row,
+            seat.column,
+            codec.encode(&seat)?
+        )?;
     }
+    Ok(())
+}
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
+fn part_1(passes: &BoardingPasses) -> Result<u64> {
+    match passes.seat_ids().max() {
+        Some(max) => Ok(max),
+        None => err!("Input is empty!"),
     }
+}
+
+fn part_2(passes: &BoardingPasses) -> Result<u64> {
+    match passes.free_seats().as_slice() {
+        [seat_id] => Ok(*seat_id),
+        [] => err!("Couldn't find santa's seat!"),
+        seat_ids => err!("Several seats could be santa's : {:?}", seat_ids),
+    }
+}
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let seats = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&seats)));
+#[cfg(test)]
+aoc_runner::examples!(
+    day(Codec::new(7, 3).unwrap()),
+    example_1,
+    example_2,
+    example_3,
+    example_4,
+    example_5
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec() -> Result<()> {
+        let codec = Codec::new(7, 3)?;
+        let seat = codec.decode("FBFBBFFRLR")?;
+        assert_eq!(seat, Seat { row: 44, column: 5 });
+        assert_eq!(codec.seat_id(&seat), 357);
+        assert_eq!(codec.seat(357), seat);
+        assert_eq!(codec.encode(&seat)?, "FBFBBFFRLR");
+        assert!(codec
+            .encode(&Seat {
+                row: 128,
+                column: 0
+            })
+            .is_err());
+        assert!(codec.decode("FBFBBFFRL").is_err());
+        assert!(codec.decode("FBFBBFFRLB").is_err());
+
+        let codec = Codec::new(2, 1)?;
+        assert_eq!(codec.decode("BFR")?, Seat { row: 2, column: 1 });
+        assert_eq!(codec.encode(&Seat { row: 3, column: 0 })?, "BBL");
+        assert!(Codec::new(60, 4).is_err());
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let mut seats = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&mut seats)));
+    #[test]
+    fn test_free_seats() -> Result<()> {
+        let codec = Codec::new(2, 1)?;
+        let passes = parse_input("FFL\nFBL\nBFL\nBBR", codec)?;
+        // Seats 1 and 3 are free with both neighbors occupied, seats 5 and 6 only have one
+        assert_eq!(passes.free_seats(), vec![1, 3]);
+        assert!(part_2(&passes).is_err());
+
+        let passes = parse_input("FFL\nFBL\nBFL\nFFR", codec)?;
+        assert_eq!(part_2(&passes)?, 3);
         Ok(())
     }
 }
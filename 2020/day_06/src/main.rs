@@ -1,24 +1,14 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:     889,796 ns/iter (+/- 52,820)
-    test bench::bench_part_1      ... bench:   1,108,069 ns/iter (+/- 84,047)
-    test bench::bench_part_2      ... bench:   1,240,781 ns/iter (+/- 89,467)
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the group separator line : cargo run --release -- --separator "" < input/input.txt
+// Sum another aggregation : cargo run --release -- --aggregate <union|intersection|symmetric-difference|at-least-K> < input/input.txt
 
-*/
-
-use std::collections::{HashMap, HashSet};
-use std::convert::TryFrom;
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -26,146 +16,232 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Debug)]
-struct Group {
-    answers: Vec<Vec<char>>,
-}
+const QUESTIONS: u32 = 26;
 
-impl Group {
-    fn get_unique_answers(&self) -> HashSet<char> {
-        let mut unique_answers = HashSet::new();
+// Questions answered "yes", question `a` being the lowest bit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Answers(u32);
 
-        for answer in self.answers.iter() {
-            for c in answer {
-                unique_answers.insert(*c);
-            }
-        }
+impl Answers {
+    const ALL: Answers = Answers((1 << QUESTIONS) - 1);
 
-        unique_answers
+    fn len(&self) -> usize {
+        self.0.count_ones() as usize
     }
 
-    fn get_common_answers(&self) -> Vec<char> {
-        let mut answers: HashMap<char, usize> = HashMap::new();
-        for (i, answer) in self.answers.iter().enumerate() {
-            for c in answer.iter() {
-                if i == 0 {
-                    answers.insert(*c, 1);
-                } else if let Some(a) = answers.get_mut(&c) {
-                    *a += 1;
-                }
-            }
+    fn union(self, other: Answers) -> Answers {
+        Answers(self.0 | other.0)
+    }
+
+    fn intersection(self, other: Answers) -> Answers {
+        Answers(self.0 & other.0)
+    }
+
+    fn symmetric_difference(self, other: Answers) -> Answers {
+        Answers(self.0 ^ other.0)
+    }
+
+    fn contains(&self, question: u32) -> bool {
+        self.0 >> question & 1 == 1
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for question in (0..QUESTIONS).filter(|&question| self.contains(question)) {
+            write!(f, "{}", (b'a' + question as u8) as char)?;
         }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregation {
+    // Questions anyone answered
+    Union,
+    // Questions everyone answered
+    Intersection,
+    // Questions an odd number of people answered
+    SymmetricDifference,
+    // Questions at least k people answered
+    AtLeast(usize),
+}
 
-        answers
-            .into_iter()
-            .filter(|&(_, v)| v == self.answers.len())
-            .map(|(k, _)| k)
-            .collect()
+impl FromStr for Aggregation {
+    type Err = Box<dyn Error>;
+
+    fn from_str(aggregation: &str) -> Result<Self> {
+        match aggregation {
+            "union" => Ok(Aggregation::Union),
+            "intersection" => Ok(Aggregation::Intersection),
+            "symmetric-difference" => Ok(Aggregation::SymmetricDifference),
+            _ => match aggregation
+                .strip_prefix("at-least-")
+                .map(str::parse::<usize>)
+            {
+                Some(Ok(k)) => Ok(Aggregation::AtLeast(k)),
+                _ => err!("Unknown aggregation : {}", aggregation),
+            },
+        }
     }
 }
 
-impl TryFrom<&str> for Group {
-    type Error = Box<dyn Error>;
-
-    fn try_from(value: &str) -> Result<Self> {
-        let mut answers = vec![];
-        for line in value.lines() {
-            let mut currents_answers = vec![];
-            for c in line.chars() {
-                match c {
-                    'a'..='z' => {
-                        currents_answers.push(c);
+#[derive(Debug)]
+struct Group {
+    // Input line of the first person of the group
+    line: usize,
+    people: Vec<Answers>,
+}
+
+impl Group {
+    fn aggregate(&self, aggregation: Aggregation) -> Answers {
+        match aggregation {
+            Aggregation::Union => self
+                .people
+                .iter()
+                .fold(Answers::default(), |all, &answers| all.union(answers)),
+            Aggregation::Intersection => self
+                .people
+                .iter()
+                .fold(Answers::ALL, |all, &answers| all.intersection(answers)),
+            Aggregation::SymmetricDifference => self
+                .people
+                .iter()
+                .fold(Answers::default(), |all, &answers| {
+                    all.symmetric_difference(answers)
+                }),
+            Aggregation::AtLeast(k) => {
+                let mut counts = [0; QUESTIONS as usize];
+                for answers in &self.people {
+                    for (question, count) in counts.iter_mut().enumerate() {
+                        *count += answers.contains(question as u32) as usize;
                     }
-                    _ => err!("Invalid input : {}", c),
                 }
+                Answers(
+                    (0..QUESTIONS)
+                        .filter(|&question| counts[question as usize] >= k)
+                        .fold(0, |mask, question| mask | 1 << question),
+                )
             }
-            answers.push(currents_answers);
         }
-        Ok(Group { answers })
     }
 }
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+// Groups are separated by lines equal to the separator, blank lines by default. Line endings,
+// including Windows ones, are never part of a line
+fn parse_groups(input: &str, separator: &str) -> Result<Vec<Group>> {
+    let mut groups: Vec<Group> = vec![];
+    let mut current: Option<Group> = None;
 
-    let groups = parse_input(&input)?;
+    for (i, line) in input.lines().enumerate() {
+        if line == separator {
+            groups.extend(current.take());
+            continue;
+        }
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&groups))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&groups))?;
-    Ok(())
-}
+        let group = current.get_or_insert_with(|| Group {
+            line: i + 1,
+            people: vec![],
+        });
+        let mut answers = Answers::default();
+        for (column, c) in line.chars().enumerate() {
+            match c {
+                'a'..='z' => answers.0 |= 1 << (c as u32 - 'a' as u32),
+                _ => err!(
+                    "Invalid answer `{}` in group {}, line {}, column {}",
+                    c,
+                    groups.len() + 1,
+                    i + 1,
+                    column + 1
+                ),
+            }
+        }
+        group.people.push(answers);
+    }
+    groups.extend(current);
 
-fn parse_input(input: &str) -> Result<Vec<Group>> {
-    input.split("\n\n").map(Group::try_from).collect()
+    Ok(groups)
 }
 
-fn part_1(groups: &[Group]) -> usize {
-    groups.iter().map(|v| v.get_unique_answers().len()).sum()
-}
+fn main() -> Result<()> {
+    let options = Options::from_env(&[Flag::Value("--separator"), Flag::Value("--aggregate")])?;
+    let separator = options.value("--separator").unwrap_or("");
+    if let Some(aggregation) = options.parse_value::<Aggregation>("--aggregate")? {
+        return print_aggregation(&options, separator, aggregation);
+    }
 
-fn part_2(groups: &[Group]) -> usize {
-    groups.iter().map(|v| v.get_common_answers().len()).sum()
+    day(separator).run_with(&options)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+fn day(separator: &str) -> Day<Vec<Group>> {
+    let separator = separator.to_string();
+    Day::new(move |input| parse_groups(input, &separator))
+        .part(|groups| Ok(sum(groups, Aggregation::Union)))
+        .part(|groups| Ok(sum(groups, Aggregation::Intersection)))
+}
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let groups = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&groups), 11);
-        Ok(())
+fn print_aggregation(options: &Options, separator: &str, aggregation: Aggregation) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let groups = parse_groups(&input, separator)?;
+
+    let mut stdout = io::stdout();
+    for (i, group) in groups.iter().enumerate() {
+        let answers = group.aggregate(aggregation);
+        writeln!(
+            stdout,
+            "Group {} (line {}) : {} ({})",
+            i + 1,
+            group.line,
+            answers.len(),
+            answers
+        )?;
     }
+    writeln!(stdout, "Sum : {}", sum(&groups, aggregation))?;
+    Ok(())
+}
 
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let groups = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(&groups), 6);
-        Ok(())
-    }
+fn sum(groups: &[Group], aggregation: Aggregation) -> usize {
+    groups
+        .iter()
+        .map(|group| group.aggregate(aggregation).len())
+        .sum()
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+#[cfg(test)]
+aoc_runner::examples!(day(""), example_1);
 
+#[cfg(test)]
+mod tests {
     use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let groups = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&groups)));
+    #[test]
+    fn test_aggregations() -> Result<()> {
+        let groups = parse_groups("abc\nabd\nae", "")?;
+        let aggregate = |aggregation| groups[0].aggregate(aggregation).to_string();
+        assert_eq!(aggregate(Aggregation::Union), "abcde");
+        assert_eq!(aggregate(Aggregation::Intersection), "a");
+        assert_eq!(aggregate(Aggregation::SymmetricDifference), "acde");
+        assert_eq!(aggregate(Aggregation::from_str("at-least-2")?), "ab");
+        assert_eq!(aggregate(Aggregation::AtLeast(0)).len(), 26);
+        assert!(Aggregation::from_str("at-least-x").is_err());
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let groups = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&groups)));
+    #[test]
+    fn test_separators() -> Result<()> {
+        let groups = parse_groups("abc\r\n\r\na\r\nb\r\n\r\n\r\nc\r\n", "")?;
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1].line, 3);
+        assert_eq!(sum(&groups, Aggregation::Union), 6);
+
+        let groups = parse_groups("ab\n--\nb\nb", "--")?;
+        assert_eq!(sum(&groups, Aggregation::Intersection), 3);
+
+        let error = parse_groups("ab\n\nb\nbC", "").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid answer `C` in group 2, line 4, column 2"
+        );
         Ok(())
     }
 }
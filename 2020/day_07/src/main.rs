@@ -1,28 +1,20 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:   6,387,662 ns/iter (+/- 142,930)
-    test bench::bench_part_1      ... bench:     116,764 ns/iter (+/- 15,101)
-    test bench::bench_part_2      ... bench:      27,432 ns/iter (+/- 2,558)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the bag : cargo run --release -- --target "shiny gold" < input/input.txt
+// Export graph : cargo run --release -- --dot <file> < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
-use std::collections::hash_map::Entry;
+use aoc_runner::{Day, Flag, Options};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -30,29 +22,224 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Debug, Clone)]
-struct Bag {
-    color: String,
-    contains: HashMap<String, usize>,
-    contained_by: HashMap<String, usize>,
+#[derive(Debug, PartialEq, Eq)]
+enum GraphError {
+    // Colors of the cycle, in containment order, the first color being contained by the last one
+    Cycle(Vec<String>),
+    UnknownColor(String),
+    Overflow(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::Cycle(colors) => write!(
+                f,
+                "Bag rules are cyclic : {} -> {}",
+                colors.join(" -> "),
+                colors[0]
+            ),
+            GraphError::UnknownColor(color) => write!(f, "Couldn't find the {} bag !", color),
+            GraphError::Overflow(color) => {
+                write!(f, "Number of bags inside the {} bag overflows", color)
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+// Bag rules as a weighted directed acyclic graph, an edge going from a container bag to every bag
+// it contains, weighted by the number of contained bags
+#[derive(Debug)]
+struct BagGraph {
+    colors: Vec<String>,
+    ids: HashMap<String, usize>,
+    contains: Vec<Vec<(usize, usize)>>,
+    contained_by: Vec<Vec<usize>>,
+    // Containers always come before the bags they contain
+    topological_order: Vec<usize>,
+    // Memoized number of bags inside every bag
+    total_contents: Vec<usize>,
+}
+
+impl BagGraph {
+    fn new(rules: Vec<(String, Vec<(String, usize)>)>) -> std::result::Result<Self, GraphError> {
+        let mut graph = BagGraph {
+            colors: vec![],
+            ids: HashMap::new(),
+            contains: vec![],
+            contained_by: vec![],
+            topological_order: vec![],
+            total_contents: vec![],
+        };
+
+        for (container, contained_bags) in rules {
+            let container = graph.id_or_insert(&container);
+            for (color, number) in contained_bags {
+                let contained = graph.id_or_insert(&color);
+                graph.contains[container].push((contained, number));
+                graph.contained_by[contained].push(container);
+            }
+        }
+
+        graph.topological_order = graph.topological_sort()?;
+        graph.total_contents = graph.count_total_contents()?;
+        Ok(graph)
+    }
+
+    fn id_or_insert(&mut self, color: &str) -> usize {
+        if let Some(&id) = self.ids.get(color) {
+            return id;
+        }
+        let id = self.colors.len();
+        self.colors.push(color.to_string());
+        self.ids.insert(color.to_string(), id);
+        self.contains.push(vec![]);
+        self.contained_by.push(vec![]);
+        id
+    }
+
+    fn id(&self, color: &str) -> std::result::Result<usize, GraphError> {
+        match self.ids.get(color) {
+            Some(&id) => Ok(id),
+            None => Err(GraphError::UnknownColor(color.to_string())),
+        }
+    }
+
+    // Depth first search, a bag met again while still being explored closes a cycle
+    fn topological_sort(&self) -> std::result::Result<Vec<usize>, GraphError> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Mark {
+            Unvisited,
+            InProgress,
+            Done,
+        }
+
+        let mut marks = vec![Mark::Unvisited; self.colors.len()];
+        let mut order = Vec::with_capacity(self.colors.len());
+
+        for root in 0..self.colors.len() {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+
+            // Path from the root along with the index of the next contained bag to explore
+            let mut path: Vec<(usize, usize)> = vec![(root, 0)];
+            marks[root] = Mark::InProgress;
+            while let Some((bag, next)) = path.last_mut() {
+                let bag = *bag;
+                match self.contains[bag].get(*next) {
+                    Some(&(contained, _)) => {
+                        *next += 1;
+                        match marks[contained] {
+                            Mark::Unvisited => {
+                                marks[contained] = Mark::InProgress;
+                                path.push((contained, 0));
+                            }
+                            Mark::InProgress => {
+                                let start = path
+                                    .iter()
+                                    .position(|&(bag, _)| bag == contained)
+                                    .unwrap_or(0);
+                                return Err(GraphError::Cycle(
+                                    path[start..]
+                                        .iter()
+                                        .map(|&(bag, _)| self.colors[bag].clone())
+                                        .collect(),
+                                ));
+                            }
+                            Mark::Done => {}
+                        }
+                    }
+                    None => {
+                        marks[bag] = Mark::Done;
+                        order.push(bag);
+                        path.pop();
+                    }
+                }
+            }
+        }
+
+        // Bags were added once everything they contain was, containers have to come first
+        order.reverse();
+        Ok(order)
+    }
+
+    fn count_total_contents(&self) -> std::result::Result<Vec<usize>, GraphError> {
+        let mut total_contents = vec![0; self.colors.len()];
+        for &bag in self.topological_order.iter().rev() {
+            let mut total: usize = 0;
+            for &(contained, number) in &self.contains[bag] {
+                total = number
+                    .checked_mul(total_contents[contained] + 1)
+                    .and_then(|bags| total.checked_add(bags))
+                    .ok_or_else(|| GraphError::Overflow(self.colors[bag].clone()))?;
+            }
+            total_contents[bag] = total;
+        }
+        Ok(total_contents)
+    }
+
+    fn containers_of(&self, color: &str) -> std::result::Result<usize, GraphError> {
+        let mut checked = vec![false; self.colors.len()];
+        let mut to_check = self.contained_by[self.id(color)?].clone();
+        let mut containers = 0;
+
+        while let Some(bag) = to_check.pop() {
+            if !checked[bag] {
+                checked[bag] = true;
+                containers += 1;
+                to_check.extend(&self.contained_by[bag]);
+            }
+        }
+        Ok(containers)
+    }
+
+    fn total_contents_of(&self, color: &str) -> std::result::Result<usize, GraphError> {
+        Ok(self.total_contents[self.id(color)?])
+    }
+
+    // Rules are written in topological order, containers first
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph bags {\n");
+        for &bag in &self.topological_order {
+            let color = &self.colors[bag];
+            if self.contains[bag].is_empty() && self.contained_by[bag].is_empty() {
+                dot.push_str(&format!("    \"{}\";\n", color));
+            }
+            for &(contained, number) in &self.contains[bag] {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    color, self.colors[contained], number
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[Flag::Value("--target"), Flag::Value("--dot")])?;
+    let target = options.value("--target").unwrap_or("shiny gold");
+    if let Some(file) = options.value("--dot") {
+        return export_dot(&options, Path::new(file));
+    }
 
-    let bags = parse_input(&input)?;
+    day(target).run_with(&options)
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&bags)?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&bags)?)?;
-    Ok(())
+fn day(target: &str) -> Day<BagGraph> {
+    let (target_1, target_2) = (target.to_string(), target.to_string());
+    Day::new(parse_input)
+        .part(move |bags| part_1(bags, &target_1))
+        .part(move |bags| part_2(bags, &target_2))
 }
 
-fn parse_input(input: &str) -> Result<HashMap<String, Bag>> {
+fn parse_input(input: &str) -> Result<BagGraph> {
     use regex::Regex;
 
-    let mut bags: HashMap<String, Bag> = HashMap::new();
-
     lazy_static! {
         static ref DAY_07_CONTAINER_BAG_REGEX: Regex =
             Regex::new(r"^(?P<container_bag_color>[a-z]+ [a-z]+) bags contain (?P<contained_bags>(?:no other bags)|(\d+ [a-z]+ [a-z]+ bag(s)?)(, \d+ [a-z]+ [a-z]+ bag(s)?)*)\.$")
@@ -62,199 +249,97 @@ fn parse_input(input: &str) -> Result<HashMap<String, Bag>> {
                 .expect("Invalid DAY_07_CONTAINED_BAG_REGEX!");
     }
 
+    let mut rules = vec![];
+    let mut containers = HashSet::new();
     for line in input.lines() {
         if let Some(cap) = DAY_07_CONTAINER_BAG_REGEX.captures(line) {
-            let current_bag = match bags.entry(cap["container_bag_color"].into()) {
-                Entry::Occupied(o) => o.into_mut(),
-                Entry::Vacant(v) => v.insert(Bag {
-                    color: cap["container_bag_color"].to_string(),
-                    contains: HashMap::new(),
-                    contained_by: HashMap::new(),
-                }),
-            };
+            if !containers.insert(cap["container_bag_color"].to_string()) {
+                err!("Bag rule defined twice : {}", &cap["container_bag_color"])
+            }
 
+            let mut contained_bags: Vec<(String, usize)> = vec![];
             if &cap["contained_bags"] != "no other bags" {
                 for subcap in DAY_07_CONTAINED_BAG_REGEX.captures_iter(&cap["contained_bags"]) {
-                    if current_bag
-                        .contains
-                        .insert(
-                            subcap["contained_bag_color"].to_string(),
-                            subcap["contained_bag_number"].parse::<usize>()?,
-                        )
-                        .is_some()
-                    {
+                    let color = subcap["contained_bag_color"].to_string();
+                    if contained_bags.iter().any(|(c, _)| *c == color) {
                         err!(
                             "Current bag already contains this bag color : {} => {}",
                             &cap["container_bag_color"],
-                            &subcap["contained_bag_color"]
+                            color
                         )
                     }
+                    contained_bags.push((color, subcap["contained_bag_number"].parse::<usize>()?));
                 }
             }
+            rules.push((cap["container_bag_color"].to_string(), contained_bags));
         } else {
             err!("Couldn't parse input line : {}", line)
         }
     }
 
-    for (bag_color, bag) in &bags.clone() {
-        // Cannot borrow as a mutable more than once, but can clone this one to update the real one
-        for (contained_bag_color, contained_bag_number) in &bag.contains {
-            let contained_bag = match bags.entry(contained_bag_color.into()) {
-                Entry::Occupied(o) => o.into_mut(),
-                Entry::Vacant(v) => v.insert(Bag {
-                    color: contained_bag_color.clone(),
-                    contains: HashMap::new(),
-                    contained_by: HashMap::new(),
-                }),
-            };
-            if contained_bag
-                .contained_by
-                .insert(bag_color.clone(), *contained_bag_number)
-                .is_some()
-            {
-                err!(
-                    "Current bag already is already contained by this bag color : {} => {}",
-                    &contained_bag_color,
-                    &bag_color
-                )
-            }
-        }
-    }
-
-    Ok(bags)
+    Ok(BagGraph::new(rules)?)
 }
 
-fn part_1(bags: &HashMap<String, Bag>) -> Result<usize> {
-    let mut bags_checked: HashSet<String> = HashSet::new();
-    let mut bags_to_check: Vec<String> = vec![];
-
-    if let Some(shiny_gold_bag) = bags.get("shiny gold") {
-        bags_to_check = shiny_gold_bag
-            .contained_by
-            .iter()
-            .map(|(k, _)| k.clone())
-            .collect();
-    } else {
-        err!("Couldn't find the shiny gold bag !")
-    }
-
-    while let Some(current_bag) = bags_to_check.pop() {
-        if bags_checked.insert(current_bag.clone()) {
-            if let Some(container_bag) = bags.get(&current_bag) {
-                bags_to_check.extend(
-                    container_bag
-                        .contained_by
-                        .iter()
-                        .map(|(k, _)| k.clone())
-                        .collect::<Vec<String>>(),
-                );
-            } else {
-                err!("Couldn't find a container bag : {}", &current_bag)
-            }
-        }
-    }
+fn export_dot(options: &Options, file: &Path) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
 
-    Ok(bags_checked.len())
+    fs::write(file, parse_input(&input)?.to_dot())?;
+    writeln!(io::stdout(), "Graph exported to \"{}\"", file.display())?;
+    Ok(())
 }
 
-fn part_2(bags: &HashMap<String, Bag>) -> Result<usize> {
-    let mut number_of_bags = 0;
-    let mut bags_to_check: Vec<(String, usize)> = vec![];
-
-    if let Some(shiny_gold_bag) = bags.get("shiny gold") {
-        bags_to_check = shiny_gold_bag
-            .contains
-            .iter()
-            .map(|(k, v)| (k.clone(), *v))
-            .collect();
-    } else {
-        err!("Couldn't find the shiny gold bag !")
-    }
-
-    while let Some((current_bag_color, current_bag_number)) = bags_to_check.pop() {
-        if let Some(contained_bag) = bags.get(&current_bag_color) {
-            number_of_bags += current_bag_number;
-            bags_to_check.extend(
-                contained_bag
-                    .contains
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v * current_bag_number))
-                    .collect::<Vec<(String, usize)>>(),
-            );
-        } else {
-            err!("Couldn't find a container bag : {}", &current_bag_color)
-        }
-    }
+fn part_1(bags: &BagGraph, target: &str) -> Result<usize> {
+    Ok(bags.containers_of(target)?)
+}
 
-    Ok(number_of_bags)
+fn part_2(bags: &BagGraph, target: &str) -> Result<usize> {
+    Ok(bags.total_contents_of(target)?)
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day("shiny gold"), example_1, example_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    fn read_test_file_2() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test2.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let bags = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&bags)?, 4);
-        Ok(())
+    fn test_cycle() {
+        let error = parse_input(
+            "light red bags contain 1 bright white bag.\n\
+             bright white bags contain 2 muted yellow bags.\n\
+             muted yellow bags contain 3 light red bags, 1 dark blue bag.\n\
+             dark blue bags contain no other bags.",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Bag rules are cyclic : light red -> bright white -> muted yellow -> light red"
+        );
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let bags = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(&bags)?, 32);
-        let bags = parse_input(&read_test_file_2()?)?;
-        assert_eq!(part_2(&bags)?, 126);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let bags = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&bags)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let bags = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&bags)));
+    fn test_topological_sort_and_dot() -> Result<()> {
+        let bags = parse_input(
+            "faded blue bags contain no other bags.\n\
+             shiny gold bags contain 2 dark red bags, 1 faded blue bag.\n\
+             dark red bags contain 3 faded blue bags.",
+        )?;
+        let order: Vec<&str> = bags
+            .topological_order
+            .iter()
+            .map(|&bag| bags.colors[bag].as_str())
+            .collect();
+        assert_eq!(order, vec!["shiny gold", "dark red", "faded blue"]);
+        assert_eq!(part_2(&bags, "shiny gold")?, 2 * (1 + 3) + 1);
+        assert!(part_2(&bags, "plaid magenta").is_err());
+        assert_eq!(
+            bags.to_dot(),
+            "digraph bags {\n    \
+             \"shiny gold\" -> \"dark red\" [label=\"2\"];\n    \
+             \"shiny gold\" -> \"faded blue\" [label=\"1\"];\n    \
+             \"dark red\" -> \"faded blue\" [label=\"3\"];\n}\n"
+        );
         Ok(())
     }
 }
@@ -1,29 +1,17 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:     606,400 ns/iter (+/- 45,221)
-    test bench::bench_part_1      ... bench:      24,318 ns/iter (+/- 4,232)
-    test bench::bench_part_2      ... bench:   1,871,495 ns/iter (+/- 25,712)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Analyze program : cargo run --release -- --analyze < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
-use std::collections::HashSet;
-use std::convert::TryFrom;
+use aoc_runner::{Day, Flag, Options};
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
-use std::io::{self, Read, Write};
-use std::str::FromStr;
+use std::io::{self, Write};
+use std::rc::Rc;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -31,205 +19,411 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Clone)]
-enum Command {
-    Nop,
-    Acc,
-    Jmp,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    position: isize,
+    accumulator: isize,
 }
 
-impl FromStr for Command {
-    type Err = Box<dyn Error>;
+// Operations must not depend on the accumulator : their effect on the position and on the
+// accumulator is computed once per instruction by the static analysis of the program
+trait Operation {
+    fn execute(&self, argument: isize, state: &mut State);
+}
 
-    fn from_str(input: &str) -> Result<Self> {
-        match input {
-            "nop" => Ok(Command::Nop),
-            "acc" => Ok(Command::Acc),
-            "jmp" => Ok(Command::Jmp),
-            _ => err!("Could not determine the command : {}", input),
-        }
+struct Nop;
+
+impl Operation for Nop {
+    fn execute(&self, _: isize, state: &mut State) {
+        state.position += 1;
     }
 }
 
-#[derive(Clone)]
-struct Processor {
-    program: Vec<(Command, isize)>,
-    current_position: isize,
-    accumulator: isize,
+struct Acc;
+
+impl Operation for Acc {
+    fn execute(&self, argument: isize, state: &mut State) {
+        state.accumulator += argument;
+        state.position += 1;
+    }
 }
 
-impl Processor {
-    fn next_step(&mut self) -> Result<()> {
-        if self.current_position >= 0 {
-            if let Some(program_line) = self.program.get(self.current_position as usize) {
-                match program_line {
-                    (Command::Nop, _) => self.current_position += 1,
-                    (Command::Acc, number) => {
-                        self.accumulator += number;
-                        self.current_position += 1;
-                    }
-                    (Command::Jmp, number) => self.current_position += number,
-                }
-            } else {
-                err!(
-                    "Program is out of bounds : Accumulator = {}; Position = {}",
-                    self.accumulator,
-                    self.current_position
-                )
-            }
-        } else {
-            err!(
-                "Program current position can't be negative : {}",
-                self.current_position
-            )
+struct Jmp;
+
+impl Operation for Jmp {
+    fn execute(&self, argument: isize, state: &mut State) {
+        state.position += argument;
+    }
+}
+
+// Operations known by the console, by mnemonic, along with the patches allowed when repairing a program
+struct InstructionSet {
+    operations: HashMap<String, Rc<dyn Operation>>,
+    patches: Vec<(String, String)>,
+}
+
+impl InstructionSet {
+    fn new() -> Self {
+        InstructionSet {
+            operations: HashMap::new(),
+            patches: vec![],
         }
-        Ok(())
     }
 
-    fn is_program_terminated(&self) -> bool {
-        self.current_position >= 0 && self.current_position as usize == self.program.len()
+    fn with_operation(mut self, mnemonic: &str, operation: impl Operation + 'static) -> Self {
+        self.operations
+            .insert(mnemonic.to_string(), Rc::new(operation));
+        self
+    }
+
+    // A patch replaces the operation `from` by the operation `to`, keeping the argument
+    fn with_patch(mut self, from: &str, to: &str) -> Self {
+        self.patches.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    fn operation(&self, mnemonic: &str) -> Result<Rc<dyn Operation>> {
+        match self.operations.get(mnemonic) {
+            Some(operation) => Ok(Rc::clone(operation)),
+            None => err!("Unknown operation : {}", mnemonic),
+        }
     }
 }
 
-impl TryFrom<&str> for Processor {
-    type Error = Box<dyn Error>;
+impl Default for InstructionSet {
+    fn default() -> Self {
+        InstructionSet::new()
+            .with_operation("nop", Nop)
+            .with_operation("acc", Acc)
+            .with_operation("jmp", Jmp)
+            .with_patch("nop", "jmp")
+            .with_patch("jmp", "nop")
+    }
+}
 
-    fn try_from(value: &str) -> Result<Self> {
+struct Instruction {
+    mnemonic: String,
+    argument: isize,
+    operation: Rc<dyn Operation>,
+}
+
+impl Instruction {
+    // Position reached and accumulator change when executing this instruction at `position`
+    fn effect(&self, position: usize) -> (isize, isize) {
+        let mut state = State {
+            position: position as isize,
+            accumulator: 0,
+        };
+        self.operation.execute(self.argument, &mut state);
+        (state.position, state.accumulator)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Terminated,
+    // Positions executed forever, starting with the first position executed twice
+    Loop { cycle: Vec<usize> },
+    OutOfBounds { position: isize },
+}
+
+#[derive(Debug)]
+struct Execution {
+    // State before every executed instruction, in order
+    trace: Vec<State>,
+    accumulator: isize,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Patch {
+    position: usize,
+    from: String,
+    to: String,
+    // Accumulator once the patched program terminates
+    accumulator: isize,
+}
+
+struct Console {
+    instruction_set: InstructionSet,
+    program: Vec<Instruction>,
+}
+
+impl Console {
+    fn new(instruction_set: InstructionSet, source: &str) -> Result<Self> {
         use regex::Regex;
         lazy_static! {
             static ref DAY_08_PROGRAM_LINE_REGEX: Regex =
-                Regex::new(r"^(?P<command>jmp|acc|nop) (?P<number>[+-]\d+)$")
+                Regex::new(r"^(?P<mnemonic>[a-z]+) (?P<argument>[+-]\d+)$")
                     .expect("Invalid DAY_08_PROGRAM_LINE_REGEX!");
         }
 
-        let mut program: Vec<(Command, isize)> = vec![];
-
-        for line in value.lines() {
+        let mut program = vec![];
+        for line in source.lines() {
             if let Some(cap) = DAY_08_PROGRAM_LINE_REGEX.captures(line) {
-                program.push((
-                    Command::from_str(&cap["command"])?,
-                    cap["number"].parse::<isize>()?,
-                ));
+                program.push(Instruction {
+                    mnemonic: cap["mnemonic"].to_string(),
+                    argument: cap["argument"].parse::<isize>()?,
+                    operation: instruction_set.operation(&cap["mnemonic"])?,
+                });
             } else {
                 err!("Couldn't parse input : {}", line)
             }
         }
 
-        Ok(Processor {
+        Ok(Console {
+            instruction_set,
             program,
-            current_position: 0,
-            accumulator: 0,
         })
     }
-}
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    fn run(&self) -> Execution {
+        // Index in the trace of the first execution of every position
+        let mut executed_at: Vec<Option<usize>> = vec![None; self.program.len()];
+        let mut trace = vec![];
+        let mut state = State {
+            position: 0,
+            accumulator: 0,
+        };
 
-    let processor = parse_input(&input)?;
+        let outcome = loop {
+            if state.position == self.program.len() as isize {
+                break Outcome::Terminated;
+            }
+            if state.position < 0 || state.position > self.program.len() as isize {
+                break Outcome::OutOfBounds {
+                    position: state.position,
+                };
+            }
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(processor.clone())?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(processor)?)?;
-    Ok(())
-}
+            let position = state.position as usize;
+            if let Some(start) = executed_at[position] {
+                let cycle = trace[start..]
+                    .iter()
+                    .map(|step: &State| step.position as usize)
+                    .collect();
+                break Outcome::Loop { cycle };
+            }
+            executed_at[position] = Some(trace.len());
+            trace.push(state);
+
+            let instruction = &self.program[position];
+            instruction
+                .operation
+                .execute(instruction.argument, &mut state);
+        };
+
+        Execution {
+            trace,
+            accumulator: state.accumulator,
+            outcome,
+        }
+    }
 
-fn parse_input(input: &str) -> Result<Processor> {
-    Processor::try_from(input)
-}
+    // Accumulator change from every position until the program terminates, None when it never does.
+    // Computed in linear time with a breadth first search from the end of the program on reversed edges.
+    fn accumulator_until_termination(&self) -> Vec<Option<isize>> {
+        let end = self.program.len();
+        let mut predecessors: Vec<Vec<(usize, isize)>> = vec![vec![]; end + 1];
+        for (position, instruction) in self.program.iter().enumerate() {
+            let (next, change) = instruction.effect(position);
+            if next >= 0 && next as usize <= end {
+                predecessors[next as usize].push((position, change));
+            }
+        }
 
-fn part_1(mut processor: Processor) -> Result<isize> {
-    let mut positions_executed: HashSet<isize> = HashSet::new();
+        let mut until_termination = vec![None; end + 1];
+        until_termination[end] = Some(0);
+        let mut queue = VecDeque::from(vec![end]);
+        while let Some(position) = queue.pop_front() {
+            let remaining = until_termination[position].unwrap_or(0);
+            for &(predecessor, change) in &predecessors[position] {
+                if until_termination[predecessor].is_none() {
+                    until_termination[predecessor] = Some(change + remaining);
+                    queue.push_back(predecessor);
+                }
+            }
+        }
 
-    while positions_executed.insert(processor.current_position) {
-        processor.next_step()?;
+        until_termination
     }
 
-    Ok(processor.accumulator)
-}
-
-fn part_2(processor: Processor) -> Result<isize> {
-    'outer: for i in 0..processor.program.len() {
-        let mut processor = processor.clone();
-        match processor.program[i] {
-            (Command::Nop, number) => processor.program[i] = (Command::Jmp, number),
-            (Command::Jmp, number) => processor.program[i] = (Command::Nop, number),
-            _ => continue,
+    // Every single instruction patch making the program terminate, found in linear time : a patch
+    // only matters on the path actually executed, and works when the patched instruction leads to
+    // a position from which the unpatched program terminates
+    fn repair(&self) -> Result<Vec<Patch>> {
+        let execution = self.run();
+        if execution.outcome == Outcome::Terminated {
+            err!("Program already terminates")
         }
+        let until_termination = self.accumulator_until_termination();
 
-        let mut positions_executed: HashSet<isize> = HashSet::new();
-        loop {
-            if processor.is_program_terminated() {
-                return Ok(processor.accumulator);
-            }
-            match positions_executed.insert(processor.current_position) {
-                true => processor.next_step()?,
-                false => continue 'outer,
+        let mut patches = vec![];
+        for step in &execution.trace {
+            let position = step.position as usize;
+            let instruction = &self.program[position];
+
+            for (from, to) in &self.instruction_set.patches {
+                if *from != instruction.mnemonic {
+                    continue;
+                }
+                let patched = Instruction {
+                    mnemonic: to.clone(),
+                    argument: instruction.argument,
+                    operation: self.instruction_set.operation(to)?,
+                };
+                let (next, change) = patched.effect(position);
+                if next < 0 || next as usize > self.program.len() {
+                    continue;
+                }
+                if let Some(remaining) = until_termination[next as usize] {
+                    patches.push(Patch {
+                        position,
+                        from: from.clone(),
+                        to: to.clone(),
+                        accumulator: step.accumulator + change + remaining,
+                    });
+                }
             }
         }
+
+        Ok(patches)
+    }
+}
+
+fn main() -> Result<()> {
+    let options = Options::from_env(&[Flag::Switch("--analyze")])?;
+    if options.has("--analyze") {
+        return analyze(&options);
     }
 
-    err!("Couldn't find a swap that lets us finish the program")
+    day().run_with(&options)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
+fn day() -> Day<Console> {
+    Day::new(parse_input).part(part_1).part(part_2)
+}
 
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+fn parse_input(input: &str) -> Result<Console> {
+    Console::new(InstructionSet::default(), input)
+}
+
+fn analyze(options: &Options) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let console = parse_input(&input)?;
+    let execution = console.run();
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "Trace :")?;
+    for state in &execution.trace {
+        let instruction = &console.program[state.position as usize];
+        writeln!(
+            stdout,
+            "    {:>5} : {} {:+} (accumulator = {})",
+            state.position, instruction.mnemonic, instruction.argument, state.accumulator
+        )?;
+    }
+    writeln!(stdout, "Outcome : {:?}", execution.outcome)?;
+
+    if execution.outcome != Outcome::Terminated {
+        writeln!(stdout, "Patches making the program terminate :")?;
+        for patch in console.repair()? {
+            writeln!(
+                stdout,
+                "    {:>5} : {} -> {} (accumulator = {})",
+                patch.position, patch.from, patch.to, patch.accumulator
+            )?;
+        }
     }
+    Ok(())
+}
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let processor = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(processor)?, 5);
-        Ok(())
+fn part_1(console: &Console) -> Result<isize> {
+    let execution = console.run();
+    match execution.outcome {
+        Outcome::Loop { .. } => Ok(execution.accumulator),
+        outcome => err!("Program should loop forever, got {:?}", outcome),
     }
+}
 
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let processor = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(processor)?, 8);
-        Ok(())
+fn part_2(console: &Console) -> Result<isize> {
+    let patches = console.repair()?;
+    match patches.as_slice() {
+        [patch] => Ok(patch.accumulator),
+        [] => err!("Couldn't find a swap that lets us finish the program"),
+        _ => err!(
+            "Several swaps let us finish the program, at positions {:?}",
+            patches
+                .iter()
+                .map(|patch| patch.position)
+                .collect::<Vec<_>>()
+        ),
     }
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1);
 
+#[cfg(test)]
+mod tests {
     use super::*;
-    use std::fs::File;
-    use test::Bencher;
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+    struct Skp;
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
+    impl Operation for Skp {
+        fn execute(&self, _: isize, state: &mut State) {
+            state.position += 2;
+        }
     }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(processor.clone())));
+    #[test]
+    fn test_loop_and_patches() -> Result<()> {
+        let console =
+            parse_input("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6")?;
+        let execution = console.run();
+        assert_eq!(
+            execution.outcome,
+            Outcome::Loop {
+                cycle: vec![1, 2, 6, 7, 3, 4]
+            }
+        );
+        assert_eq!(execution.trace.len(), 7);
+
+        assert_eq!(
+            console.repair()?,
+            vec![Patch {
+                position: 7,
+                from: "jmp".to_string(),
+                to: "nop".to_string(),
+                accumulator: 8
+            }]
+        );
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(processor.clone())));
+    #[test]
+    fn test_extended_instruction_set() -> Result<()> {
+        let instruction_set = InstructionSet::default()
+            .with_operation("skp", Skp)
+            .with_patch("skp", "nop");
+        let console = Console::new(instruction_set, "skp +0\njmp +4\nacc +5\nacc +1\njmp -4")?;
+        assert_eq!(
+            console.run().outcome,
+            Outcome::Loop {
+                cycle: vec![0, 2, 3, 4]
+            }
+        );
+
+        // Both replacing the skip and removing the last jump let the program terminate
+        let patches = console.repair()?;
+        assert_eq!(
+            patches
+                .iter()
+                .map(|patch| (patch.position, patch.accumulator))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (4, 6)]
+        );
+        assert!(parse_input("skp +0").is_err());
         Ok(())
     }
 }
@@ -1,22 +1,14 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:       5,684 ns/iter (+/- 435)
-    test bench::bench_part_1      ... bench:         811 ns/iter (+/- 44)
-    test bench::bench_part_2      ... bench:       1,292 ns/iter (+/- 47)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the maximum step : cargo run --release -- --max-step 3 < input/input.txt
+// Print the gap distribution : cargo run --release -- --gaps < input/input.txt
 
+use aoc_runner::{Day, Flag, Options};
+use std::collections::{BTreeMap, VecDeque};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::io::{self, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -24,160 +16,208 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    let adapters = parse_input(&input)?;
-
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&adapters)?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&adapters)?)?;
-    Ok(())
+// Unsigned integer without any upper bound, only supporting additions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Count {
+    // Base 2^64 digits, least significant first
+    limbs: Vec<u64>,
 }
 
-fn parse_input(input: &str) -> Result<Vec<usize>> {
-    let mut adapters = vec![];
-
-    for line in input.lines() {
-        adapters.push(line.parse::<usize>()?);
+impl Count {
+    fn one() -> Self {
+        Count { limbs: vec![1] }
     }
 
-    adapters.sort_unstable();
-
-    Ok(adapters)
+    fn add(&mut self, other: &Count) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (sum, overflow_1) = limb.overflowing_add(other.limbs.get(i).copied().unwrap_or(0));
+            let (sum, overflow_2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = overflow_1 || overflow_2;
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
 }
 
-fn part_1(adapters: &[usize]) -> Result<usize> {
-    let mut difference_1 = 0;
-    let mut difference_3 = 1;
-
-    for i in 0..adapters.len() {
-        let difference = if i == 0 {
-            adapters[i]
-        } else {
-            adapters[i] - adapters[i - 1]
-        };
-        match difference {
-            0 | 2 => {}
-            1 => difference_1 += 1,
-            3 => difference_3 += 1,
-            other_value => err!(
-                "Difference between two adapters can't be greater than 3 : {}",
-                other_value
-            ),
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+
+        // Repeated divisions by 10^19, each remainder giving 19 decimal digits
+        let mut limbs = self.limbs.clone();
+        let mut chunks = vec![];
+        while limbs.iter().any(|&limb| limb != 0) {
+            let mut remainder: u128 = 0;
+            for limb in limbs.iter_mut().rev() {
+                let current = (remainder << 64) | *limb as u128;
+                *limb = (current / CHUNK) as u64;
+                remainder = current % CHUNK;
+            }
+            chunks.push(remainder);
+        }
+
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, others)) => {
+                write!(f, "{}", first)?;
+                for chunk in others.iter().rev() {
+                    write!(f, "{:019}", chunk)?;
+                }
+                Ok(())
+            }
         }
     }
+}
 
-    Ok(difference_1 * difference_3)
+// Sorted joltages, from the outlet (0) to the device (highest adapter + maximum step)
+struct Chain {
+    joltages: Vec<u64>,
+    max_step: u64,
 }
 
-fn part_2(adapters: &[usize]) -> Result<usize> {
-    let mut differences = vec![];
-    let mut ones_found = 0;
-    let mut result = 1;
-
-    for i in 0..adapters.len() {
-        let difference = if i == 0 {
-            adapters[i]
-        } else {
-            adapters[i] - adapters[i - 1]
-        };
-        differences.push(difference);
+impl Chain {
+    fn new(adapters: &[u64], max_step: u64) -> Self {
+        let device = adapters.last().map_or(0, |&highest| highest) + max_step;
+        let mut joltages = Vec::with_capacity(adapters.len() + 2);
+        joltages.push(0);
+        joltages.extend_from_slice(adapters);
+        joltages.push(device);
+        Chain { joltages, max_step }
     }
-    differences.push(3);
-
-    /*
-    Not quite happy with this solution.
-    In the case of adapters with 5 or more consecutive diff of 1 (eg:"(0), 1, 2, 3, 4, 5, 6, (9)"), the answer would be wrong.
-    But I can't find any case where this happens
-    */
-
-    for diff in differences {
-        if diff == 1 {
-            ones_found += 1;
-        } else {
-            match ones_found {
-                0 | 1 => {}
-                2 => result *= 2,
-                3 => result *= 4,
-                4 => result *= 7,
-                _ => err!("Too many consecutive differences of 1 found, need to update the algorithm!"),
+
+    // Number of gaps of every size when using all the adapters
+    fn gap_distribution(&self) -> Result<BTreeMap<u64, usize>> {
+        let mut gaps = BTreeMap::new();
+        for pair in self.joltages.windows(2) {
+            let gap = pair[1] - pair[0];
+            if gap > self.max_step {
+                err!(
+                    "Can't use every adapter : no adapter between {} and {} jolts",
+                    pair[0],
+                    pair[1]
+                )
             }
-            ones_found = 0;
+            *gaps.entry(gap).or_insert(0) += 1;
         }
+        Ok(gaps)
     }
 
-    Ok(result)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
+    // Arrangements going from the outlet to every joltage, adding up the ways of every joltage at
+    // most `max_step` below. Only those joltages are kept, in a sliding window
+    fn arrangements(&self) -> Count {
+        let mut window: VecDeque<(u64, Count)> = VecDeque::new();
+        window.push_back((self.joltages[0], Count::one()));
+
+        for &joltage in &self.joltages[1..] {
+            while window
+                .front()
+                .is_some_and(|&(reachable, _)| joltage - reachable > self.max_step)
+            {
+                window.pop_front();
+            }
+            let mut current = Count::default();
+            for (_, previous) in &window {
+                current.add(previous);
+            }
+            window.push_back((joltage, current));
+        }
 
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+        window.pop_back().map(|(_, ways)| ways).unwrap_or_default()
     }
+}
 
-    fn read_test_file_2() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test2.txt")?.read_to_string(&mut input)?;
-        Ok(input)
+fn main() -> Result<()> {
+    let options = Options::from_env(&[Flag::Value("--max-step"), Flag::Switch("--gaps")])?;
+    let max_step = match options.parse_value::<u64>("--max-step")? {
+        Some(0) => err!("--max-step expects a strictly positive number"),
+        Some(step) => step,
+        None => 3,
+    };
+    if options.has("--gaps") {
+        return print_gaps(&options, max_step);
     }
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let adapters = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&adapters)?, 7 * 5);
-        let adapters = parse_input(&read_test_file_2()?)?;
-        assert_eq!(part_1(&adapters)?, 22 * 10);
-        Ok(())
+    day(max_step).run_with(&options)
+}
+
+fn day(max_step: u64) -> Day<Vec<u64>> {
+    Day::new(parse_input)
+        .part(move |adapters| part_1(adapters, max_step))
+        .part(move |adapters| part_2(adapters, max_step))
+}
+
+fn parse_input(input: &str) -> Result<Vec<u64>> {
+    let mut adapters = vec![];
+
+    for line in input.lines() {
+        adapters.push(line.parse::<u64>()?);
     }
 
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let adapters = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(&adapters)?, 8);
-        let adapters = parse_input(&read_test_file_2()?)?;
-        assert_eq!(part_2(&adapters)?, 19208);
-        Ok(())
+    adapters.sort_unstable();
+
+    Ok(adapters)
+}
+
+fn print_gaps(options: &Options, max_step: u64) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let chain = Chain::new(&parse_input(&input)?, max_step);
+
+    let mut stdout = io::stdout();
+    for (gap, count) in chain.gap_distribution()? {
+        writeln!(stdout, "{} jolt(s) : {}", gap, count)?;
     }
+    Ok(())
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+// Gaps of 1 jolt multiplied by gaps of the maximum step
+fn part_1(adapters: &[u64], max_step: u64) -> Result<usize> {
+    let gaps = Chain::new(adapters, max_step).gap_distribution()?;
+    let count = |gap| gaps.get(&gap).copied().unwrap_or(0);
+    Ok(count(1) * count(max_step))
+}
 
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
+fn part_2(adapters: &[u64], max_step: u64) -> Result<Count> {
+    Ok(Chain::new(adapters, max_step).arrangements())
+}
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+#[cfg(test)]
+aoc_runner::examples!(day(3), example_1, example_2, example_3);
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let adapters = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&adapters)));
+    #[test]
+    fn test_long_runs() -> Result<()> {
+        let adapters: Vec<u64> = (1..=200).collect();
+        assert_eq!(
+            part_2(&adapters, 3)?.to_string(),
+            "52622583840983769603765180599790256716084480555530641"
+        );
+        assert_eq!(part_1(&adapters, 3)?, 200);
+
+        // Fibonacci numbers when every step is at most 2 jolts
+        let adapters: Vec<u64> = (1..=10).collect();
+        assert_eq!(part_2(&adapters, 2)?.to_string(), "89");
+        assert_eq!(part_2(&[], 3)?.to_string(), "1");
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let adapters = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&adapters)));
+    #[test]
+    fn test_gaps() -> Result<()> {
+        let adapters = parse_input("1\n4\n4\n6")?;
+        let gaps = Chain::new(&adapters, 3).gap_distribution()?;
+        assert_eq!(
+            gaps.into_iter().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 1), (2, 1), (3, 2)]
+        );
+        assert!(part_1(&parse_input("1\n5")?, 3).is_err());
         Ok(())
     }
 }
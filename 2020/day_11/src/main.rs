@@ -1,23 +1,15 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:      94,294 ns/iter (+/- 7,283)
-    test bench::bench_part_1      ... bench:  70,056,476 ns/iter (+/- 4,620,083)
-    test bench::bench_part_2      ... bench: 112,152,263 ns/iter (+/- 3,398,333)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Export history : cargo run --release -- --history <directory> < input/input.txt
 
+use aoc_runner::{Day, Flag, Options};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -25,117 +17,270 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+static DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1), // North West
+    (-1, 0),  // West
+    (-1, 1),  // South West
+    (0, -1),  // South
+    (0, 1),   // North
+    (1, -1),  // North East
+    (1, 0),   // East
+    (1, 1),   // South East
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum CellState {
     EmptySeat,
     OccupiedSeat,
     Floor,
 }
 
+#[derive(Clone, Copy)]
+enum NeighborPolicy {
+    Adjacent,
+    // A seat sees the first seat in each direction, looking at most `max_range` cells away
+    LineOfSight { max_range: Option<usize> },
+}
+
+#[derive(Clone, Copy)]
+struct Rules {
+    neighbor_policy: NeighborPolicy,
+    tolerance: usize,
+    wrap_around: bool,
+}
+
+impl Rules {
+    fn max_range(&self) -> Option<usize> {
+        match self.neighbor_policy {
+            NeighborPolicy::Adjacent => Some(1),
+            NeighborPolicy::LineOfSight { max_range } => max_range,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    // Layout of round `round` is repeated forever
+    Stable { round: usize },
+    // Layouts from round `start` repeat every `period` rounds
+    Periodic { start: usize, period: usize },
+}
+
 #[derive(Clone)]
 struct Seats {
-    cells: Vec<Vec<CellState>>,
-    line_length: usize,
+    cells: Vec<CellState>,
+    width: usize,
+    height: usize,
 }
 
 impl Seats {
-    fn execute_rounds(&mut self, tolerance: usize, only_see_adjacent_seats: bool) {
-        loop {
-            let mut new_cells = self.cells.clone();
-            let mut placement_has_changed = false;
-            for (i, cell_line) in self.cells.iter().enumerate() {
-                for (j, cell) in cell_line.iter().enumerate() {
-                    if *cell == CellState::Floor {
-                        continue
-                    }
-                    let occupied_seats = if only_see_adjacent_seats {
-                        self.get_adjacent_occupied_seats(j, i)
-                    } else {
-                        self.get_visible_occupied_seats(j, i)
-                    };
-                    if *cell == CellState::EmptySeat && occupied_seats == 0 {
-                        new_cells[i][j] = CellState::OccupiedSeat;
-                        placement_has_changed = true;
-                    } else if *cell == CellState::OccupiedSeat && occupied_seats >= tolerance {
-                        new_cells[i][j] = CellState::EmptySeat;
-                        placement_has_changed = true;
+    fn seat_positions(&self) -> Vec<usize> {
+        (0..self.cells.len())
+            .filter(|&position| self.cells[position] != CellState::Floor)
+            .collect()
+    }
+
+    // For every seat, the list of seats (as indexes into `seat_positions`) it takes into account
+    fn compute_visibility(&self, rules: &Rules, seat_positions: &[usize]) -> Vec<Vec<usize>> {
+        let mut seat_indexes = vec![usize::MAX; self.cells.len()];
+        for (i, &position) in seat_positions.iter().enumerate() {
+            seat_indexes[position] = i;
+        }
+
+        let width = self.width as isize;
+        let height = self.height as isize;
+
+        seat_positions
+            .iter()
+            .map(|&position| {
+                let origin = (
+                    (position % self.width) as isize,
+                    (position / self.width) as isize,
+                );
+                let mut visible_seats = vec![];
+
+                for direction in DIRECTIONS.iter() {
+                    let (mut x, mut y) = origin;
+                    let mut range = 0;
+
+                    loop {
+                        if rules
+                            .max_range()
+                            .is_some_and(|max_range| range >= max_range)
+                        {
+                            break;
+                        }
+                        range += 1;
+                        x += direction.0;
+                        y += direction.1;
+
+                        if rules.wrap_around {
+                            x = x.rem_euclid(width);
+                            y = y.rem_euclid(height);
+                        } else if x < 0 || x >= width || y < 0 || y >= height {
+                            break;
+                        }
+                        if (x, y) == origin {
+                            break;
+                        }
+
+                        let seat_index = seat_indexes[(y * width + x) as usize];
+                        if seat_index != usize::MAX {
+                            visible_seats.push(seat_index);
+                            break;
+                        }
                     }
                 }
-            }
 
-            self.cells = new_cells;
+                // With wrap around, several directions can reach the same seat on narrow layouts
+                visible_seats.sort_unstable();
+                visible_seats.dedup();
+                visible_seats
+            })
+            .collect()
+    }
 
-            if !placement_has_changed {
-                break;
+    fn simulate(&self, rules: &Rules) -> History {
+        let seat_positions = self.seat_positions();
+        let visibility = self.compute_visibility(rules, &seat_positions);
+
+        let mut current: Vec<bool> = seat_positions
+            .iter()
+            .map(|&position| self.cells[position] == CellState::OccupiedSeat)
+            .collect();
+        let mut next = current.clone();
+
+        let mut seen_layouts = HashMap::new();
+        let mut rounds = vec![];
+
+        let outcome = loop {
+            let snapshot = pack(&current);
+            if let Some(&start) = seen_layouts.get(&snapshot) {
+                let period = rounds.len() - start;
+                if period == 1 {
+                    break Outcome::Stable { round: start };
+                }
+                break Outcome::Periodic { start, period };
+            }
+            seen_layouts.insert(snapshot.clone(), rounds.len());
+            rounds.push(snapshot);
+
+            for (i, visible_seats) in visibility.iter().enumerate() {
+                let occupied_seats = visible_seats.iter().filter(|&&j| current[j]).count();
+                next[i] = if current[i] {
+                    occupied_seats < rules.tolerance
+                } else {
+                    occupied_seats == 0
+                };
             }
+            std::mem::swap(&mut current, &mut next);
+        };
+
+        History {
+            seats: self.clone(),
+            seat_positions,
+            rounds,
+            outcome,
         }
     }
+}
 
-    fn get_adjacent_occupied_seats(&self, x: usize, y: usize) -> usize {
-        let directions = vec![
-            (-1, -1),   // North West
-            (-1, 0),    // West
-            (-1, 1),    // South West
-            (0, -1),    // South
-            (0, 1),     // North
-            (1, -1),    // North East
-            (1, 0),     // East
-            (1, 1),     // South East
-        ];
-        let mut adjacent_occupied_seats = 0;
-
-        for direction in directions {
-            let current_x = x as isize + direction.0;
-            let current_y = y as isize + direction.1;
-
-            if current_x >= 0
-                && self.line_length > current_x as usize
-                && current_y >= 0
-                && self.cells.len() > current_y as usize
-                && self.cells[current_y as usize][current_x as usize] == CellState::OccupiedSeat
-            {
-                adjacent_occupied_seats += 1;
-            }
+fn pack(occupied: &[bool]) -> Vec<u64> {
+    let mut packed = vec![0; occupied.len().div_ceil(64)];
+    for (i, _) in occupied.iter().enumerate().filter(|(_, &o)| o) {
+        packed[i / 64] |= 1 << (i % 64);
+    }
+    packed
+}
+
+struct History {
+    seats: Seats,
+    seat_positions: Vec<usize>,
+    // Every distinct layout encountered, as packed occupancy bits of each seat
+    rounds: Vec<Vec<u64>>,
+    outcome: Outcome,
+}
+
+impl History {
+    fn layout(&self, round: usize) -> Vec<CellState> {
+        let mut cells = self.seats.cells.clone();
+        for (i, &position) in self.seat_positions.iter().enumerate() {
+            cells[position] = if self.rounds[round][i / 64] & (1 << (i % 64)) != 0 {
+                CellState::OccupiedSeat
+            } else {
+                CellState::EmptySeat
+            };
         }
+        cells
+    }
 
-        adjacent_occupied_seats
+    fn occupied_seats(&self, round: usize) -> usize {
+        self.rounds[round]
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
     }
 
-    fn get_visible_occupied_seats(&self, x: usize, y: usize) -> usize {
-        let directions = vec![
-            (-1, -1),   // North West
-            (-1, 0),    // West
-            (-1, 1),    // South West
-            (0, -1),    // South
-            (0, 1),     // North
-            (1, -1),    // North East
-            (1, 0),     // East
-            (1, 1),     // South East
-        ];
-        let mut visible_occupied_seats = 0;
-        'outer: for direction in directions {
-            let mut current_x = x as isize + direction.0;
-            let mut current_y = y as isize + direction.1;
-
-            while current_x >= 0
-                && self.line_length > current_x as usize
-                && current_y >= 0
-                && self.cells.len() > current_y as usize
-            {
-                match self.cells[current_y as usize][current_x as usize] {
-                    CellState::OccupiedSeat => {
-                        visible_occupied_seats += 1;
-                        continue 'outer;
-                    }
-                    CellState::EmptySeat => continue 'outer,
-                    _ => {}
-                }
+    fn final_occupied_seats(&self) -> Result<usize> {
+        match self.outcome {
+            Outcome::Stable { round } => Ok(self.occupied_seats(round)),
+            Outcome::Periodic { start, period } => err!(
+                "Seats never stabilize : layout oscillates every {} rounds from round {}",
+                period,
+                start
+            ),
+        }
+    }
 
-                current_x += direction.0;
-                current_y += direction.1;
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for round in 0..self.rounds.len() {
+            text.push_str(&format!("Round {} :\n", round));
+            for line in self.layout(round).chunks(self.seats.width) {
+                text.extend(line.iter().map(|cell| match cell {
+                    CellState::Floor => '.',
+                    CellState::EmptySeat => 'L',
+                    CellState::OccupiedSeat => '#',
+                }));
+                text.push('\n');
             }
+            text.push('\n');
         }
-        visible_occupied_seats
+        text.push_str(&match self.outcome {
+            Outcome::Stable { round } => format!("Stable from round {}\n", round),
+            Outcome::Periodic { start, period } => {
+                format!("Oscillates every {} rounds from round {}\n", period, start)
+            }
+        });
+        text
+    }
+
+    fn export(&self, directory: &Path, name: &str) -> Result<()> {
+        use image::png::PNGEncoder;
+        use image::ColorType;
+
+        fs::create_dir_all(directory)?;
+        fs::write(directory.join(format!("{}.txt", name)), self.to_text())?;
+
+        for round in 0..self.rounds.len() {
+            let pixels: Vec<u8> = self
+                .layout(round)
+                .iter()
+                .map(|cell| match cell {
+                    CellState::Floor => 0,
+                    CellState::EmptySeat => 127,
+                    CellState::OccupiedSeat => 255,
+                })
+                .collect();
+            let output = File::create(directory.join(format!("{}_round_{:04}.png", name, round)))?;
+            PNGEncoder::new(output).encode(
+                &pixels,
+                self.seats.width as u32,
+                self.seats.height as u32,
+                ColorType::Gray(8),
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -144,40 +289,73 @@ impl TryFrom<&str> for Seats {
 
     fn try_from(value: &str) -> Result<Self> {
         let mut cells = vec![];
-        let mut line_length = 0;
+        let mut width = 0;
+        let mut height = 0;
 
         for (i, line) in value.lines().enumerate() {
             if i == 0 {
-                line_length = line.len();
-            } else if line.len() != line_length {
+                width = line.len();
+            } else if line.len() != width {
                 err!("Invalid input : every line should have the same length")
             }
-            let mut cells_line = vec![];
 
             for cell in line.chars() {
                 match cell {
-                    '.' => cells_line.push(CellState::Floor),
-                    'L' => cells_line.push(CellState::EmptySeat),
-                    '#' => cells_line.push(CellState::OccupiedSeat),
+                    '.' => cells.push(CellState::Floor),
+                    'L' => cells.push(CellState::EmptySeat),
+                    '#' => cells.push(CellState::OccupiedSeat),
                     other_char => err!("Invalid characted found : {}", other_char),
                 }
             }
 
-            cells.push(cells_line);
+            height += 1;
         }
 
-        Ok(Seats { cells, line_length })
+        Ok(Seats {
+            cells,
+            width,
+            height,
+        })
     }
 }
 
+const RULES_PART_1: Rules = Rules {
+    neighbor_policy: NeighborPolicy::Adjacent,
+    tolerance: 4,
+    wrap_around: false,
+};
+
+const RULES_PART_2: Rules = Rules {
+    neighbor_policy: NeighborPolicy::LineOfSight { max_range: None },
+    tolerance: 5,
+    wrap_around: false,
+};
+
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[Flag::Value("--history")])?;
+    if let Some(directory) = options.value("--history") {
+        return export_history(&options, Path::new(directory));
+    }
 
-    let seats = parse_input(&input)?;
+    day().run_with(&options)
+}
+
+fn day() -> Day<Seats> {
+    Day::new(parse_input).part(part_1).part(part_2)
+}
+
+fn export_history(options: &Options, directory: &Path) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(seats.clone()))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(seats))?;
+    let seats = parse_input(&input)?;
+    seats.simulate(&RULES_PART_1).export(directory, "part_1")?;
+    seats.simulate(&RULES_PART_2).export(directory, "part_2")?;
+
+    writeln!(
+        io::stdout(),
+        "History exported to \"{}\"",
+        directory.display()
+    )?;
     Ok(())
 }
 
@@ -185,84 +363,64 @@ fn parse_input(input: &str) -> Result<Seats> {
     Seats::try_from(input)
 }
 
-fn part_1(mut seats: Seats) -> usize {
-    seats.execute_rounds(4, true);
-    seats
-        .cells
-        .iter()
-        .flatten()
-        .filter(|&v| *v == CellState::OccupiedSeat)
-        .count()
+fn part_1(seats: &Seats) -> Result<usize> {
+    seats.simulate(&RULES_PART_1).final_occupied_seats()
 }
 
-fn part_2(mut seats: Seats) -> usize {
-    seats.execute_rounds(5, false);
-    seats
-        .cells
-        .iter()
-        .flatten()
-        .filter(|&v| *v == CellState::OccupiedSeat)
-        .count()
+fn part_2(seats: &Seats) -> Result<usize> {
+    seats.simulate(&RULES_PART_2).final_occupied_seats()
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let seats = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(seats), 37);
+    fn test_oscillating_layout() -> Result<()> {
+        let seats = parse_input("LL")?;
+        let rules = Rules {
+            neighbor_policy: NeighborPolicy::Adjacent,
+            tolerance: 1,
+            wrap_around: false,
+        };
+        let history = seats.simulate(&rules);
+        assert_eq!(
+            history.outcome,
+            Outcome::Periodic {
+                start: 0,
+                period: 2
+            }
+        );
+        assert!(history.final_occupied_seats().is_err());
         Ok(())
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let seats = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(seats), 26);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let seats = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(seats.clone())));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let seats = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(seats.clone())));
+    fn test_wrap_around() -> Result<()> {
+        let seats = parse_input("L.L\n...\nL.L")?;
+        let rules = Rules {
+            neighbor_policy: NeighborPolicy::LineOfSight { max_range: Some(1) },
+            tolerance: 1,
+            wrap_around: true,
+        };
+        // Every corner sees the 3 other corners once edges wrap around
+        let visibility = seats.compute_visibility(&rules, &seats.seat_positions());
+        assert!(visibility
+            .iter()
+            .all(|visible_seats| visible_seats.len() == 3));
+
+        // Six directions lead to the other seat of a 2x1 layout, it only counts once
+        let seats = parse_input("LL")?;
+        let rules = Rules {
+            neighbor_policy: NeighborPolicy::Adjacent,
+            tolerance: 1,
+            wrap_around: true,
+        };
+        let visibility = seats.compute_visibility(&rules, &seats.seat_positions());
+        assert_eq!(visibility, vec![vec![1], vec![0]]);
         Ok(())
     }
 }
@@ -1,23 +1,16 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Render both paths as SVG : cargo run --release -- --svg <file> < input/input.txt
 
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:      37,525 ns/iter (+/- 1,678)
-    test bench::bench_part_1      ... bench:      12,950 ns/iter (+/- 1,050)
-    test bench::bench_part_2      ... bench:      12,034 ns/iter (+/- 930)
+mod vector;
 
-*/
-
-use std::convert::TryFrom;
+use aoc_runner::{Day, Flag, Options};
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::{self, Write};
+use vector::{Coordinate, Vector};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -25,238 +18,287 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Clone)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+// Angles are in degrees, and don't have to be multiples of 90
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction<T> {
+    North(T),
+    East(T),
+    South(T),
+    West(T),
+    Forward(T),
+    Right(T),
+    Left(T),
 }
 
-impl Direction {
-    fn to_angle(&self) -> isize {
-        match self {
-            Direction::North => 90,
-            Direction::East => 0,
-            Direction::South => 270,
-            Direction::West => 180,
+impl Instruction<f64> {
+    // Same instruction with integer values, as long as the value is whole and the rotation a
+    // multiple of 90°
+    fn whole(&self) -> Option<Instruction<i64>> {
+        let (value, right_angle) = match *self {
+            Instruction::Right(angle) | Instruction::Left(angle) => (angle, angle % 90.0 == 0.0),
+            Instruction::North(value)
+            | Instruction::East(value)
+            | Instruction::South(value)
+            | Instruction::West(value)
+            | Instruction::Forward(value) => (value, true),
+        };
+        // Beyond 2^53, floats can't tell consecutive integers apart
+        if !right_angle || value.fract() != 0.0 || value.abs() > (1u64 << 53) as f64 {
+            return None;
         }
-    }
 
-    fn from_angle(mut angle: isize) -> Result<Self> {
-        angle %= 360;
-        if angle < 0 {
-            angle += 360;
-        }
-
-        match angle {
-            90 => Ok(Direction::North),
-            0 => Ok(Direction::East),
-            270 => Ok(Direction::South),
-            180 => Ok(Direction::West),
-            _ => err!("Angle could not determine a direction : {}", angle),
-        }
+        let value = value as i64;
+        Some(match self {
+            Instruction::North(_) => Instruction::North(value),
+            Instruction::East(_) => Instruction::East(value),
+            Instruction::South(_) => Instruction::South(value),
+            Instruction::West(_) => Instruction::West(value),
+            Instruction::Forward(_) => Instruction::Forward(value),
+            Instruction::Right(_) => Instruction::Right(value),
+            Instruction::Left(_) => Instruction::Left(value),
+        })
     }
 }
 
-#[derive(Clone)]
-enum Instruction {
-    North(isize),
-    East(isize),
-    South(isize),
-    West(isize),
-    Forward(isize),
-    Right(isize),
-    Left(isize),
+// What the cardinal instructions move : the ship itself, or a waypoint relative to the ship
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Ship,
+    Waypoint,
 }
 
-#[derive(Clone)]
-struct Ship {
-    current_direction: Direction,
-    current_position: (isize, isize),
-    current_waypoint: (isize, isize),
-    instructions: Vec<Instruction>,
+#[derive(Debug, Clone)]
+struct Navigation<T> {
+    mode: Mode,
+    position: Vector<T>,
+    // Moving forward adds this vector to the position : the ship direction, or the waypoint
+    heading: Vector<T>,
+    // Positions of the ship, starting at the origin, each time it moved
+    path: Vec<Vector<T>>,
 }
 
-impl Ship {
-    fn execute_instructions(&mut self) -> Result<()> {
-        for instruction in &self.instructions {
-            match instruction {
-                Instruction::North(number) => self.current_position.1 += number,
-                Instruction::East(number) => self.current_position.0 += number,
-                Instruction::South(number) => self.current_position.1 -= number,
-                Instruction::West(number) => self.current_position.0 -= number,
-                Instruction::Forward(number) => match self.current_direction {
-                    Direction::North => self.current_position.1 += number,
-                    Direction::East => self.current_position.0 += number,
-                    Direction::South => self.current_position.1 -= number,
-                    Direction::West => self.current_position.0 -= number,
-                },
-                Instruction::Left(number) => {
-                    self.current_direction =
-                        Direction::from_angle(self.current_direction.to_angle() + number)?
-                }
-                Instruction::Right(number) => {
-                    self.current_direction =
-                        Direction::from_angle(self.current_direction.to_angle() - number)?
-                }
-            }
+impl<T: Coordinate> Navigation<T> {
+    fn new(mode: Mode) -> Self {
+        let heading = match mode {
+            Mode::Ship => Vector::east(),
+            Mode::Waypoint => Vector::new(T::from(10), T::from(1)),
+        };
+        Navigation {
+            mode,
+            position: Vector::default(),
+            heading,
+            path: vec![Vector::default()],
         }
-        Ok(())
     }
 
-    fn execute_instructions_with_waypoint(&mut self) -> Result<()> {
-        for instruction in &self.instructions {
-            match instruction {
-                Instruction::North(number) => self.current_waypoint.1 += number,
-                Instruction::East(number) => self.current_waypoint.0 += number,
-                Instruction::South(number) => self.current_waypoint.1 -= number,
-                Instruction::West(number) => self.current_waypoint.0 -= number,
-                Instruction::Forward(number) => {
-                    for _ in 0..*number {
-                        self.current_position.0 += self.current_waypoint.0;
-                        self.current_position.1 += self.current_waypoint.1;
-                    }
-                }
-                Instruction::Left(number) => {
-                    self.current_waypoint = self.get_rotated_waypoint(false, *number)?
-                }
-                Instruction::Right(number) => {
-                    self.current_waypoint = self.get_rotated_waypoint(true, *number)?
-                }
-            }
+    fn run(mode: Mode, instructions: &[Instruction<T>]) -> Self {
+        let mut navigation = Navigation::new(mode);
+        for &instruction in instructions {
+            navigation.execute(instruction);
         }
-        Ok(())
+        navigation
     }
 
-    fn get_rotated_waypoint(&self, rotate_right: bool, mut angle: isize) -> Result<(isize, isize)> {
-        if rotate_right {
-            angle *= -1;
-        }
-        angle %= 360;
-
-        match angle {
-            0 => Ok(self.current_waypoint),
-            -270 | 90 => Ok((-self.current_waypoint.1, self.current_waypoint.0)),
-            -180 | 180 => Ok((-self.current_waypoint.0, -self.current_waypoint.1)),
-            -90 | 270 => Ok((self.current_waypoint.1, -self.current_waypoint.0)),
-            _ => err!("Invalid angle found : {}", angle),
-        }
-    }
-}
-
-impl TryFrom<&str> for Ship {
-    type Error = Box<dyn Error>;
-
-    fn try_from(value: &str) -> Result<Self> {
-        let mut instructions: Vec<Instruction> = vec![];
-
-        for line in value.lines() {
-            let number = line[1..].parse::<isize>()?;
-            match &line[..1] {
-                "N" => instructions.push(Instruction::North(number)),
-                "E" => instructions.push(Instruction::East(number)),
-                "S" => instructions.push(Instruction::South(number)),
-                "W" => instructions.push(Instruction::West(number)),
-                "F" => instructions.push(Instruction::Forward(number)),
-                "R" => instructions.push(Instruction::Right(number)),
-                "L" => instructions.push(Instruction::Left(number)),
-                other_char => err!("Invalid instruction char : {}", other_char),
+    fn execute(&mut self, instruction: Instruction<T>) {
+        let translation = match instruction {
+            Instruction::North(number) => Vector::north() * number,
+            Instruction::East(number) => Vector::east() * number,
+            Instruction::South(number) => -Vector::north() * number,
+            Instruction::West(number) => -Vector::east() * number,
+            Instruction::Forward(number) => {
+                self.move_ship(self.heading * number);
+                return;
+            }
+            Instruction::Left(angle) => {
+                self.heading = self.heading.rotate(angle);
+                return;
             }
+            Instruction::Right(angle) => {
+                self.heading = self.heading.rotate(-angle);
+                return;
+            }
+        };
+
+        match self.mode {
+            Mode::Ship => self.move_ship(translation),
+            Mode::Waypoint => self.heading += translation,
         }
+    }
 
-        Ok(Ship {
-            current_direction: Direction::East,
-            current_position: (0, 0),
-            current_waypoint: (10, 1),
-            instructions,
-        })
+    fn move_ship(&mut self, translation: Vector<T>) {
+        self.position += translation;
+        self.path.push(self.position);
     }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    let ship = parse_input(&input)?;
+    let options = Options::from_env(&[Flag::Value("--svg")])?;
+    if let Some(file) = options.value("--svg") {
+        return write_svg(&options, file);
+    }
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(ship.clone())?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(ship)?)?;
-    Ok(())
+    day().run_with(&options)
 }
 
-fn parse_input(input: &str) -> Result<Ship> {
-    Ship::try_from(input)
+fn day() -> Day<Vec<Instruction<f64>>> {
+    Day::new(parse_input)
+        .part(|instructions| Ok(part_1(instructions)))
+        .part(|instructions| Ok(part_2(instructions)))
 }
 
-fn part_1(mut ship: Ship) -> Result<isize> {
-    ship.execute_instructions()?;
-    Ok(ship.current_position.0.abs() + ship.current_position.1.abs())
-}
+fn parse_input(input: &str) -> Result<Vec<Instruction<f64>>> {
+    let mut instructions = vec![];
 
-fn part_2(mut ship: Ship) -> Result<isize> {
-    ship.execute_instructions_with_waypoint()?;
-    Ok(ship.current_position.0.abs() + ship.current_position.1.abs())
-}
+    for line in input.lines() {
+        let mut chars = line.chars();
+        let action = chars.next();
+        let number = chars.as_str().parse::<f64>()?;
+        if !number.is_finite() {
+            err!("Invalid instruction value : {}", line)
+        }
+        instructions.push(match action {
+            Some('N') => Instruction::North(number),
+            Some('E') => Instruction::East(number),
+            Some('S') => Instruction::South(number),
+            Some('W') => Instruction::West(number),
+            Some('F') => Instruction::Forward(number),
+            Some('R') => Instruction::Right(number),
+            Some('L') => Instruction::Left(number),
+            _ => err!("Invalid instruction : {}", line),
+        });
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
+    Ok(instructions)
+}
 
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+fn write_svg(options: &Options, file: &str) -> Result<()> {
+    let input = aoc_runner::read_input(options)?;
+    let instructions = parse_input(&input)?;
+
+    let paths = [
+        (Navigation::run(Mode::Ship, &instructions).path, "steelblue"),
+        (
+            Navigation::run(Mode::Waypoint, &instructions).path,
+            "crimson",
+        ),
+    ];
+    fs::write(file, svg(&paths))?;
+    writeln!(io::stdout(), "Paths saved to \"{}\"", file)?;
+    Ok(())
+}
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let ship = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(ship)?, 25);
-        Ok(())
+// One polyline per path, the north being at the top of the picture
+fn svg(paths: &[(Vec<Vector<f64>>, &str)]) -> String {
+    let points = paths.iter().flat_map(|(path, _)| path.iter());
+    let (min_x, max_x, min_y, max_y) = points.fold(
+        (0f64, 0f64, 0f64, 0f64),
+        |(min_x, max_x, min_y, max_y), point| {
+            (
+                min_x.min(point.x),
+                max_x.max(point.x),
+                min_y.min(point.y),
+                max_y.max(point.y),
+            )
+        },
+    );
+    let margin = (max_x - min_x).max(max_y - min_y).max(1.0) / 50.0;
+
+    let mut svg = String::new();
+    // Writing to a String can't fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min_x - margin,
+        -max_y - margin,
+        max_x - min_x + 2.0 * margin,
+        max_y - min_y + 2.0 * margin
+    );
+    for (path, color) in paths {
+        let points: Vec<String> = path
+            .iter()
+            // SVG y axis goes down. Adding 0 turns -0 into 0
+            .map(|point| format!("{},{}", point.x, -point.y + 0.0))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke"/>"#,
+            points.join(" "),
+            color
+        );
     }
+    let _ = writeln!(
+        svg,
+        r#"  <circle cx="0" cy="0" r="{}" fill="black"/>"#,
+        margin / 2.0
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
 
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let ship = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(ship)?, 286);
-        Ok(())
+// Manhattan distance travelled by the ship, computed on integers as long as every value is whole
+// and every rotation a multiple of 90°. Other headings go through floats and give the closest integer
+fn distance(mode: Mode, instructions: &[Instruction<f64>]) -> i64 {
+    let whole: Option<Vec<Instruction<i64>>> =
+        instructions.iter().map(Instruction::whole).collect();
+    match whole {
+        Some(instructions) => Navigation::run(mode, &instructions).position.manhattan(),
+        None => Navigation::run(mode, instructions)
+            .position
+            .manhattan()
+            .round() as i64,
     }
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+fn part_1(instructions: &[Instruction<f64>]) -> i64 {
+    distance(Mode::Ship, instructions)
+}
 
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
+fn part_2(instructions: &[Instruction<f64>]) -> i64 {
+    distance(Mode::Waypoint, instructions)
+}
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1);
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(processor.clone())));
+    #[test]
+    fn test_navigation() -> Result<()> {
+        let instructions = parse_input("F10\nN3\nF7\nR90\nF11")?;
+        let navigation = Navigation::run(Mode::Ship, &instructions);
+        assert_eq!(navigation.position, Vector::new(17.0, -8.0));
+        assert_eq!(navigation.path.len(), 5);
+
+        let navigation = Navigation::run(Mode::Waypoint, &instructions);
+        assert_eq!(navigation.position, Vector::new(214.0, -72.0));
+        assert_eq!(navigation.heading, Vector::new(4.0, -10.0));
+        assert_eq!(navigation.path.len(), 4);
+
+        // Half a turn in four steps of 45°, then three full turns the other way
+        let instructions = parse_input(&format!("{}R1080\nF1", "L45\n".repeat(4)))?;
+        let position = Navigation::run(Mode::Ship, &instructions).position;
+        assert!((position.x + 1.0).abs() < 1e-12 && position.y.abs() < 1e-12);
+        assert_eq!(part_1(&instructions), 1);
+        assert_eq!(instructions[0].whole(), None);
+        assert_eq!(instructions[4].whole(), Some(Instruction::Right(1080)));
+        // 10 * (cos 30° + sin 30°) = 13.66
+        assert_eq!(part_1(&parse_input("L30\nF10")?), 14);
+        // Too big for a float to hold exactly, right angles stay on integers
+        let instructions = parse_input("N100000000\nF100000001\nR270")?;
+        assert_eq!(part_2(&instructions), 10_000_001_200_000_011);
+        assert!(parse_input("X10").is_err());
+        assert!(parse_input("F").is_err());
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(processor.clone())));
+    #[test]
+    fn test_svg() -> Result<()> {
+        let path = Navigation::run(Mode::Ship, &parse_input("F10\nN3")?).path;
+        let svg = svg(&[(path, "black")]);
+        assert!(svg.contains(r#"points="0,0 10,0 10,-3""#));
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
         Ok(())
     }
 }
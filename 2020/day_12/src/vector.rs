@@ -0,0 +1,139 @@
+use std::ops::{Add, AddAssign, Mul, Neg};
+
+// Integer coordinates stay exact but can only turn by multiples of 90°, float ones can point
+// anywhere
+pub trait Coordinate:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + From<i32>
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    // Counterclockwise rotation
+    fn rotate(vector: Vector<Self>, degrees: Self) -> Vector<Self>;
+}
+
+impl Coordinate for i64 {
+    fn rotate(vector: Vector<i64>, degrees: i64) -> Vector<i64> {
+        debug_assert_eq!(degrees % 90, 0);
+        vector.quarter_turns(degrees / 90)
+    }
+}
+
+impl Coordinate for f64 {
+    // Multiples of 90° only swap and negate coordinates, whatever the number of turns, any other
+    // angle goes through sine and cosine
+    fn rotate(vector: Vector<f64>, degrees: f64) -> Vector<f64> {
+        let quarter_turns = degrees / 90.0;
+        if quarter_turns.fract() == 0.0 {
+            vector.quarter_turns(quarter_turns.rem_euclid(4.0) as i64)
+        } else {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            Vector::new(
+                vector.x * cos - vector.y * sin,
+                vector.x * sin + vector.y * cos,
+            )
+        }
+    }
+}
+
+// Plane vector, x going east and y going north
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vector<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coordinate> Vector<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Vector { x, y }
+    }
+
+    pub fn east() -> Self {
+        Vector::new(T::from(1), T::from(0))
+    }
+
+    pub fn north() -> Self {
+        Vector::new(T::from(0), T::from(1))
+    }
+
+    pub fn manhattan(&self) -> T {
+        let abs = |value: T| if value < T::from(0) { -value } else { value };
+        abs(self.x) + abs(self.y)
+    }
+
+    pub fn rotate(&self, degrees: T) -> Self {
+        T::rotate(*self, degrees)
+    }
+
+    fn quarter_turns(&self, turns: i64) -> Self {
+        match turns.rem_euclid(4) {
+            0 => *self,
+            1 => Vector::new(-self.y, self.x),
+            2 => -*self,
+            _ => Vector::new(self.y, -self.x),
+        }
+    }
+}
+
+impl<T: Coordinate> Add for Vector<T> {
+    type Output = Vector<T>;
+
+    fn add(self, other: Vector<T>) -> Vector<T> {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Vector<T> {
+    fn add_assign(&mut self, other: Vector<T>) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Vector<T> {
+    type Output = Vector<T>;
+
+    fn mul(self, factor: T) -> Vector<T> {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<T: Coordinate> Neg for Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(self) -> Vector<T> {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        let vector = Vector::new(10.0, 4.0);
+        assert_eq!(vector.rotate(90.0), Vector::new(-4.0, 10.0));
+        assert_eq!(vector.rotate(-90.0), Vector::new(4.0, -10.0));
+        assert_eq!(vector.rotate(450.0), vector.rotate(90.0));
+        assert_eq!(vector.rotate(-540.0), -vector);
+        assert_eq!(vector.rotate(0.0), vector);
+
+        let rotated = Vector::east().rotate(45.0) * 2f64.sqrt();
+        assert!((rotated.x - 1.0).abs() < 1e-12 && (rotated.y - 1.0).abs() < 1e-12);
+        assert_eq!((Vector::north() * 3.0 + Vector::east()).manhattan(), 4.0);
+    }
+
+    #[test]
+    fn test_rotate_integers() {
+        let vector = Vector::new(10, 4);
+        assert_eq!(vector.rotate(90), Vector::new(-4, 10));
+        assert_eq!(vector.rotate(-90), Vector::new(4, -10));
+        assert_eq!(vector.rotate(450), vector.rotate(90));
+        assert_eq!(vector.rotate(-540), -vector);
+        assert_eq!((Vector::north() * -3 + Vector::east()).manhattan(), 4);
+    }
+}
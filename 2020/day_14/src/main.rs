@@ -1,28 +1,15 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:     877,667 ns/iter (+/- 37,690)
-    test bench::bench_part_1      ... bench:     671,550 ns/iter (+/- 120,493)
-    test bench::bench_part_2      ... bench:  73,346,001 ns/iter (+/- 1,989,818)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
-use std::collections::HashMap;
+use aoc_runner::Day;
 use std::convert::TryFrom;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::str::FromStr;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -30,84 +17,168 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-#[derive(Clone)]
+const ADDRESS_BITS: u32 = 36;
+const ADDRESS_MASK: u64 = (1 << ADDRESS_BITS) - 1;
+
+// Every bit of a mask is either forced to 0, forced to 1 or floating (X)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mask {
+    and: u64,
+    or: u64,
+    floating: u64,
+}
+
+impl Mask {
+    fn apply_to_value(&self, value: u64) -> u64 {
+        (value & self.and) | self.or
+    }
+
+    fn apply_to_address(&self, address: u64) -> AddressPattern {
+        AddressPattern::new((address | self.or) & ADDRESS_MASK, self.floating)
+    }
+}
+
+impl FromStr for Mask {
+    type Err = Box<dyn Error>;
+
+    fn from_str(input: &str) -> Result<Self> {
+        if input.len() != ADDRESS_BITS as usize {
+            err!("Mask should be {} bits long : {}", ADDRESS_BITS, input)
+        }
+
+        let mut mask = Mask {
+            and: ADDRESS_MASK,
+            or: 0,
+            floating: 0,
+        };
+        for (i, bit) in input.chars().rev().enumerate() {
+            match bit {
+                '0' => mask.and &= !(1 << i),
+                '1' => mask.or |= 1 << i,
+                'X' => mask.floating |= 1 << i,
+                _ => err!("Invalid bit in mask : {}", input),
+            }
+        }
+        Ok(mask)
+    }
+}
+
+// Every address matching `fixed` on the bits which are not floating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AddressPattern {
+    fixed: u64,
+    floating: u64,
+}
+
+impl AddressPattern {
+    fn new(address: u64, floating: u64) -> Self {
+        AddressPattern {
+            fixed: address & !floating,
+            floating,
+        }
+    }
+
+    fn len(&self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    fn intersects(&self, other: &AddressPattern) -> bool {
+        let fixed_in_both = !self.floating & !other.floating;
+        (self.fixed ^ other.fixed) & fixed_in_both == 0
+    }
+
+    // Disjoint patterns covering the addresses of self which are not in other, at most one per
+    // bit floating in self but fixed in other
+    fn subtract(&self, other: &AddressPattern) -> Vec<AddressPattern> {
+        if !self.intersects(other) {
+            return vec![*self];
+        }
+
+        let mut pieces = vec![];
+        let mut remaining = *self;
+        let mut bits = self.floating & !other.floating;
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= !bit;
+
+            // Addresses whose bit differs from other are not in other, the others go on
+            remaining.floating &= !bit;
+            pieces.push(AddressPattern {
+                fixed: remaining.fixed | (!other.fixed & bit),
+                floating: remaining.floating,
+            });
+            remaining.fixed |= other.fixed & bit;
+        }
+        pieces
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decoder {
+    // Version 1 : the mask applies to the value written
+    Value,
+    // Version 2 : the mask applies to the address, floating bits taking every value
+    Address,
+}
+
+// Written regions are kept disjoint : every write removes its addresses from the older regions,
+// so that the memory is summed without enumerating floating addresses
+#[derive(Debug, Default)]
+struct Memory {
+    regions: Vec<(AddressPattern, u64)>,
+}
+
+impl Memory {
+    fn write(&mut self, pattern: AddressPattern, value: u64) {
+        let mut regions = Vec::with_capacity(self.regions.len() + 1);
+        for (region, region_value) in &self.regions {
+            for piece in region.subtract(&pattern) {
+                regions.push((piece, *region_value));
+            }
+        }
+        regions.push((pattern, value));
+        self.regions = regions;
+    }
+
+    fn sum(&self) -> u128 {
+        self.regions
+            .iter()
+            .map(|(region, value)| region.len() as u128 * *value as u128)
+            .sum()
+    }
+}
+
 enum Command {
-    Mask(Vec<char>),
-    Mem(usize, usize),
+    Mask(Mask),
+    Mem(u64, u64),
 }
 
-#[derive(Clone)]
 struct System {
     program: Vec<Command>,
-    current_mask: Vec<char>,
-    floating_bits: usize,
-    memory: HashMap<usize, usize>,
 }
 
 impl System {
-    fn execute_program(&mut self, decode_memory_address: bool) -> Result<()> {
+    fn execute_program(&self, decoder: Decoder) -> Memory {
+        let mut memory = Memory::default();
+        let mut current_mask = Mask {
+            and: ADDRESS_MASK,
+            or: 0,
+            floating: ADDRESS_MASK,
+        };
+
         for command in &self.program {
-            match command {
-                Command::Mask(mask) => {
-                    self.current_mask = mask.to_vec();
-                    self.floating_bits = self.current_mask.iter().filter(|&v| *v == 'X').count();
-                }
-                Command::Mem(address, value) => {
-                    if decode_memory_address {
-                        let masked_address = self
-                            .current_mask
-                            .iter()
-                            .zip(format!("{:036b}", address).chars())
-                            .map(|(&mask, address)| if mask == '0' { address } else { mask })
-                            .collect::<Vec<char>>();
-
-                        for i in 0..2_u64.pow(self.floating_bits as u32) {
-                            let mut bits_to_replace =
-                                format!("{:b}", i).chars().collect::<Vec<char>>();
-                            let decoded_address = masked_address
-                                .iter()
-                                .rev()
-                                .map(|&v| {
-                                    if v == 'X' {
-                                        match bits_to_replace.pop() {
-                                            Some(b) => b,
-                                            None => '0',
-                                        }
-                                    } else {
-                                        v
-                                    }
-                                })
-                                .rev()
-                                .collect::<String>();
-                            if let Ok(resulting_address) =
-                                usize::from_str_radix(&decoded_address, 2)
-                            {
-                                self.memory.insert(resulting_address, *value);
-                            } else {
-                                err!(
-                                    "Could not parse binary address to usize : {:?}",
-                                    decoded_address
-                                )
-                            }
-                        }
-                    } else {
-                        let masked_value = self
-                            .current_mask
-                            .iter()
-                            .zip(format!("{:036b}", value).chars())
-                            .map(|(&mask, value)| if mask == 'X' { value } else { mask })
-                            .collect::<String>();
-
-                        if let Ok(resulting_value) = usize::from_str_radix(&masked_value, 2) {
-                            self.memory.insert(*address, resulting_value);
-                        } else {
-                            err!("Could not parse binary value to usize : {:?}", masked_value)
-                        }
-                    }
-                }
+            match *command {
+                Command::Mask(mask) => current_mask = mask,
+                Command::Mem(address, value) => match decoder {
+                    Decoder::Value => memory.write(
+                        AddressPattern::new(address, 0),
+                        current_mask.apply_to_value(value),
+                    ),
+                    Decoder::Address => memory.write(current_mask.apply_to_address(address), value),
+                },
             }
         }
-        Ok(())
+        memory
     }
 }
 
@@ -129,7 +200,7 @@ impl TryFrom<&str> for System {
                 match &cap["command"] {
                     "mask" => {
                         if let Some(mask) = cap.name("mask") {
-                            program.push(Command::Mask(mask.as_str().chars().collect()));
+                            program.push(Command::Mask(Mask::from_str(mask.as_str())?));
                         } else {
                             err!("Invalid input mask : {}", line)
                         }
@@ -137,8 +208,8 @@ impl TryFrom<&str> for System {
                     _ => {
                         if let Some(value) = cap.name("value") {
                             program.push(Command::Mem(
-                                cap["address"].parse::<usize>()?,
-                                value.as_str().parse::<usize>()?,
+                                cap["address"].parse::<u64>()?,
+                                value.as_str().parse::<u64>()?,
                             ))
                         } else {
                             err!("Invalid input memory value : {}", line)
@@ -150,104 +221,59 @@ impl TryFrom<&str> for System {
             }
         }
 
-        Ok(System {
-            program,
-            current_mask: vec!['X'; 36],
-            floating_bits: 36,
-            memory: HashMap::new(),
-        })
+        Ok(System { program })
     }
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    let system = parse_input(&input)?;
+    day().run()
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(system.clone())?)?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(system)?)?;
-    Ok(())
+fn day() -> Day<System> {
+    Day::new(parse_input).part(part_1).part(part_2)
 }
 
 fn parse_input(input: &str) -> Result<System> {
     System::try_from(input)
 }
 
-fn part_1(mut system: System) -> Result<usize> {
-    system.execute_program(false)?;
-    Ok(system.memory.iter().map(|(_, &v)| v).sum())
+fn part_1(system: &System) -> Result<u128> {
+    Ok(system.execute_program(Decoder::Value).sum())
 }
 
-fn part_2(mut system: System) -> Result<usize> {
-    system.execute_program(true)?;
-    Ok(system.memory.iter().map(|(_, &v)| v).sum())
+fn part_2(system: &System) -> Result<u128> {
+    Ok(system.execute_program(Decoder::Address).sum())
 }
 
+#[cfg(test)]
+aoc_runner::examples!(day(), example_1, example_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    fn read_test_file_2() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test2.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let system = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(system)?, 165);
+    fn test_floating_addresses() -> Result<()> {
+        // Every single address is written by the first write, then one of them is overwritten
+        let system = parse_input(
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\nmem[0] = 3\n\
+             mask = 000000000000000000000000000000000000\nmem[42] = 10",
+        )?;
+        assert_eq!(part_2(&system)?, 3 * ((1 << 36) - 1) + 10);
         Ok(())
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let system = parse_input(&read_test_file_2()?)?;
-        assert_eq!(part_2(system)?, 208);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let system = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(system.clone())));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let system = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(system.clone())));
-        Ok(())
+    fn test_subtract() {
+        let pattern = AddressPattern::new(0, 0b111);
+        let pieces = pattern.subtract(&AddressPattern::new(0b101, 0b010));
+        assert_eq!(pieces.iter().map(AddressPattern::len).sum::<u64>(), 6);
+        assert!(pieces
+            .iter()
+            .all(|piece| !piece.intersects(&AddressPattern::new(0b101, 0b010))));
+        assert_eq!(
+            pattern.subtract(&AddressPattern::new(0b1000, 0)),
+            vec![pattern]
+        );
     }
 }
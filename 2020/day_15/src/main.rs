@@ -1,23 +1,14 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:         347 ns/iter (+/- 38)
-    test bench::bench_part_1      ... bench:     121,703 ns/iter (+/- 6,267)
-    test bench::bench_part_2      ... bench: 3,809,101,967 ns/iter (+/- 314,283,380)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Number spoken at any turn : cargo run --release -- --turn <turn> [--resume <file>] [--checkpoint <file>] < input/input.txt
+// Compare with the HashMap implementation : cargo run --release -- --compare < input/input.txt
 
+use aoc_runner::{bench, Day, Flag, Options};
 use std::collections::HashMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -25,34 +16,274 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
+// Numbers below this limit get their last turn stored in a dense array, bigger ones in a HashMap
+const DENSE_LIMIT: usize = 1 << 26;
+const CHECKPOINT_MAGIC: &[u8; 8] = b"VANECK01";
+
+// Van Eck sequence : after the starting numbers, every number spoken is the age of the previous
+// one, i.e. how many turns ago it was last spoken, or 0 if it never was
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VanEck {
+    starting: Vec<u64>,
+    // Turn a number was last spoken, 0 if it never was. Values grow up to the number of turns, so
+    // they are stored on a u32 until the turns don't fit any more
+    dense: Vec<u32>,
+    dense_limit: usize,
+    sparse: HashMap<u64, u64>,
+    // Number of turns already spoken, along with the number spoken on the last one
+    turn: u64,
+    last: Option<u64>,
+}
+
+impl VanEck {
+    fn new(starting: &[u64]) -> Self {
+        VanEck::with_dense_limit(starting, DENSE_LIMIT)
+    }
+
+    fn with_dense_limit(starting: &[u64], dense_limit: usize) -> Self {
+        VanEck {
+            starting: starting.to_vec(),
+            dense: vec![],
+            dense_limit,
+            sparse: HashMap::new(),
+            turn: 0,
+            last: None,
+        }
+    }
+
+    // Records the turn a number was spoken, returning the turn it was spoken before
+    fn record(&mut self, number: u64, turn: u64) -> Option<u64> {
+        if turn > u32::MAX as u64 && self.dense_limit > 0 {
+            // Turns don't fit in the dense array any more, every number moves to the HashMap
+            for (number, &turn) in self.dense.iter().enumerate() {
+                if turn != 0 {
+                    self.sparse.insert(number as u64, turn as u64);
+                }
+            }
+            self.dense = vec![];
+            self.dense_limit = 0;
+        }
+
+        if number < self.dense_limit as u64 {
+            let index = number as usize;
+            if index >= self.dense.len() {
+                let len = (index + 1).max(self.dense.len() * 2).min(self.dense_limit);
+                self.dense.resize(len, 0);
+            }
+            let previous = std::mem::replace(&mut self.dense[index], turn as u32);
+            if previous == 0 {
+                None
+            } else {
+                Some(previous as u64)
+            }
+        } else {
+            self.sparse.insert(number, turn)
+        }
+    }
+
+    // Number spoken on a given turn, the first one being turn 1
+    fn spoken_at(&mut self, turn: u64) -> Result<u64> {
+        if turn == 0 || turn < self.turn {
+            err!(
+                "Can't go back to turn {}, {} turns were already spoken",
+                turn,
+                self.turn
+            )
+        }
+        if self.starting.is_empty() {
+            err!("Need at least one starting number")
+        }
+
+        while self.turn < turn {
+            self.next();
+        }
+        match self.last {
+            Some(last) => Ok(last),
+            None => err!("No number spoken yet"),
+        }
+    }
+
+    // Checkpoint layout, every integer being little endian : magic, turn, last number (u64::MAX
+    // when none), then starting numbers, dense array and HashMap, each prefixed by its length
+    fn save(&self, writer: &mut impl Write) -> Result<()> {
+        writer.write_all(CHECKPOINT_MAGIC)?;
+        for value in &[
+            self.turn,
+            self.last.unwrap_or(u64::MAX),
+            self.dense_limit as u64,
+            self.starting.len() as u64,
+        ] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for number in &self.starting {
+            writer.write_all(&number.to_le_bytes())?;
+        }
+        writer.write_all(&(self.dense.len() as u64).to_le_bytes())?;
+        for turn in &self.dense {
+            writer.write_all(&turn.to_le_bytes())?;
+        }
+        writer.write_all(&(self.sparse.len() as u64).to_le_bytes())?;
+        for (number, turn) in &self.sparse {
+            writer.write_all(&number.to_le_bytes())?;
+            writer.write_all(&turn.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn load(reader: &mut impl Read) -> Result<Self> {
+        fn read_u64(reader: &mut impl Read) -> Result<u64> {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        }
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CHECKPOINT_MAGIC {
+            err!("Invalid checkpoint : unknown format")
+        }
+
+        let turn = read_u64(reader)?;
+        let last = match read_u64(reader)? {
+            u64::MAX => None,
+            last => Some(last),
+        };
+        let dense_limit = read_u64(reader)? as usize;
+        let starting = (0..read_u64(reader)?)
+            .map(|_| read_u64(reader))
+            .collect::<Result<Vec<u64>>>()?;
+
+        let dense_len = read_u64(reader)?;
+        if dense_len > dense_limit as u64 {
+            err!("Invalid checkpoint : dense array is bigger than its limit")
+        }
+        let mut dense = Vec::with_capacity(dense_len as usize);
+        for _ in 0..dense_len {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            dense.push(u32::from_le_bytes(bytes));
+        }
+        let mut sparse = HashMap::new();
+        for _ in 0..read_u64(reader)? {
+            let number = read_u64(reader)?;
+            sparse.insert(number, read_u64(reader)?);
+        }
+
+        Ok(VanEck {
+            starting,
+            dense,
+            dense_limit,
+            sparse,
+            turn,
+            last,
+        })
+    }
+}
+
+// Numbers spoken, turn after turn
+impl Iterator for VanEck {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let turn = self.turn;
+        let next = match self.last {
+            None => *self.starting.first()?,
+            Some(last) => {
+                let previous = self.record(last, turn);
+                match self.starting.get(turn as usize) {
+                    Some(&number) => number,
+                    None => previous.map_or(0, |previous| turn - previous),
+                }
+            }
+        };
+
+        self.turn += 1;
+        self.last = Some(next);
+        Some(next)
+    }
+}
+
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let options = Options::from_env(&[
+        Flag::Value("--turn"),
+        Flag::Value("--resume"),
+        Flag::Value("--checkpoint"),
+        Flag::Switch("--compare"),
+    ])?;
+    if let Some(turn) = options.parse_value::<u64>("--turn")? {
+        return speak(
+            &options,
+            turn,
+            options.value("--resume"),
+            options.value("--checkpoint"),
+        );
+    }
+    if options.has("--compare") {
+        return compare(&options);
+    }
 
-    let numbers = parse_input(&input)?;
+    day().run_with(&options)
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&numbers))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&numbers))?;
-    Ok(())
+fn day() -> Day<Vec<u64>> {
+    Day::new(parse_input)
+        .part(|numbers| part_1(numbers))
+        .part(|numbers| part_2(numbers))
 }
 
-fn parse_input(input: &str) -> Result<Vec<usize>> {
+fn parse_input(input: &str) -> Result<Vec<u64>> {
     let mut numbers = vec![];
-    for number in input.split(',') {
-        numbers.push(number.parse::<usize>()?);
+    for number in input.trim().split(',') {
+        numbers.push(number.parse::<u64>()?);
     }
     Ok(numbers)
 }
 
-fn part_1(numbers: &[usize]) -> usize {
-    execute_turns(numbers, 2020)
+fn speak(
+    options: &Options,
+    turn: u64,
+    resume: Option<&str>,
+    checkpoint: Option<&str>,
+) -> Result<()> {
+    let mut van_eck = match resume {
+        Some(file) => VanEck::load(&mut BufReader::new(File::open(file)?))?,
+        None => VanEck::new(&parse_input(&aoc_runner::read_input(options)?)?),
+    };
+
+    let number = van_eck.spoken_at(turn)?;
+    writeln!(io::stdout(), "Turn {} : {}", turn, number)?;
+
+    if let Some(file) = checkpoint {
+        let mut writer = BufWriter::new(File::create(file)?);
+        van_eck.save(&mut writer)?;
+        writer.flush()?;
+        writeln!(io::stdout(), "Checkpoint saved to \"{}\"", file)?;
+    }
+    Ok(())
 }
 
-fn part_2(numbers: &[usize]) -> usize {
-    execute_turns(numbers, 30000000)
+fn compare(options: &Options) -> Result<()> {
+    let numbers = parse_input(&aoc_runner::read_input(options)?)?;
+    let numbers_usize: Vec<usize> = numbers.iter().map(|&number| number as usize).collect();
+
+    writeln!(io::stdout(), "Comparison with the HashMap implementation :")?;
+    for &turns in &[2020, 30_000_000] {
+        let engine = bench::measure(|| VanEck::new(&numbers).spoken_at(turns));
+        let hash_map = bench::measure(|| execute_turns_hash_map(&numbers_usize, turns as usize));
+        writeln!(
+            io::stdout(),
+            "    {:>10} turns : engine {:>15.0} ns/iter, HashMap {:>15.0} ns/iter ({:.1}x)",
+            turns,
+            engine.median,
+            hash_map.median,
+            hash_map.median / engine.median
+        )?;
+    }
+    Ok(())
 }
 
-fn execute_turns(numbers: &[usize], final_turn: usize) -> usize {
+// Former implementation, only kept to compare both of them
+fn execute_turns_hash_map(numbers: &[usize], final_turn: usize) -> usize {
     let mut spoken_numbers: HashMap<usize, usize> = HashMap::new();
     let mut last_number_spoken = 0;
 
@@ -73,64 +304,72 @@ fn execute_turns(numbers: &[usize], final_turn: usize) -> usize {
     last_number_spoken
 }
 
+fn part_1(numbers: &[u64]) -> Result<u64> {
+    VanEck::new(numbers).spoken_at(2020)
+}
+
+fn part_2(numbers: &[u64]) -> Result<u64> {
+    VanEck::new(numbers).spoken_at(30_000_000)
+}
+
+#[cfg(test)]
+aoc_runner::examples!(
+    day(),
+    example_1,
+    example_2,
+    example_3,
+    example_4,
+    example_5,
+    example_6,
+    example_7
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
     #[test]
-    fn test_part_1() -> Result<()> {
-        let numbers = parse_input(&read_test_file()?)?;
-        assert_eq!(execute_turns(&numbers, 10), 0);
-        Ok(())
+    fn test_sequence() {
+        let spoken: Vec<u64> = VanEck::new(&[0, 3, 6]).take(10).collect();
+        assert_eq!(spoken, vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+        assert_eq!(VanEck::new(&[]).next(), None);
+        assert!(VanEck::new(&[]).spoken_at(1).is_err());
     }
 
     #[test]
-    fn test_part_2() -> Result<()> {
-        let numbers = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(&numbers), 175594);
+    fn test_sparse_fallback() -> Result<()> {
+        // Only the HashMap, then only the dense array for numbers below 10
+        for &limit in &[0, 10] {
+            let mut van_eck = VanEck::with_dense_limit(&[3, 1, 2], limit);
+            assert_eq!(van_eck.spoken_at(2020)?, 1836);
+            assert_eq!(
+                van_eck.spoken_at(10_000)? as usize,
+                execute_turns_hash_map(&[3, 1, 2], 10_000)
+            );
+        }
         Ok(())
     }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
+    #[test]
+    fn test_checkpoint() -> Result<()> {
+        let mut van_eck = VanEck::new(&[0, 3, 6]);
+        van_eck.spoken_at(1000)?;
+        let mut checkpoint = vec![];
+        van_eck.save(&mut checkpoint)?;
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let numbers = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&numbers)));
+        let mut resumed = VanEck::load(&mut checkpoint.as_slice())?;
+        assert_eq!(resumed, van_eck);
+        assert_eq!(resumed.spoken_at(2020)?, 436);
+        assert!(resumed.spoken_at(1000).is_err());
+        assert!(VanEck::load(&mut &b"VANECK00"[..]).is_err());
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let numbers = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&numbers)));
+    // 30 million turns take a while without optimizations : cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn test_part_2() -> Result<()> {
+        assert_eq!(part_2(&[0, 3, 6])?, 175594);
         Ok(())
     }
 }
@@ -1,27 +1,18 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:     395,472 ns/iter (+/- 30,503)
-    test bench::bench_part_1      ... bench:      53,094 ns/iter (+/- 2,143)
-    test bench::bench_part_2      ... bench:     728,510 ns/iter (+/- 38,054)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
+// Choose the fields of part 2 : cargo run --release -- --prefix departure < input/input.txt
 
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
 
-use std::collections::HashMap;
+use aoc_runner::{Day, Flag, Options};
+use std::collections::{HashSet, VecDeque};
+use std::convert::TryFrom;
 use std::error::Error;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::ops::RangeInclusive;
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -29,252 +20,465 @@ macro_rules! err {
     ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
 }
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+#[derive(Debug, PartialEq, Eq)]
+enum AssignmentError {
+    // Fields left without any column
+    Impossible(Vec<String>),
+    // Fields which could be assigned to several columns
+    Ambiguous(Vec<String>),
+}
 
-    let (ticket_rules, my_ticket, nearby_tickets) = parse_input(&input)?;
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssignmentError::Impossible(fields) => {
+                write!(f, "No column can be assigned to : {}", fields.join(", "))
+            }
+            AssignmentError::Ambiguous(fields) => write!(
+                f,
+                "Several columns could be assigned to : {}",
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for AssignmentError {}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(&ticket_rules, &nearby_tickets))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(&ticket_rules, &my_ticket, &nearby_tickets))?;
-    Ok(())
+#[derive(Debug)]
+struct Rule {
+    field: String,
+    ranges: Vec<RangeInclusive<u64>>,
 }
 
-fn parse_input(
-    input: &str,
-) -> Result<(
-    HashMap<String, ((usize, usize), (usize, usize))>,
-    Vec<usize>,
-    Vec<Vec<usize>>,
-)> {
-    let mut ticket_rules: HashMap<String, ((usize, usize), (usize, usize))> = HashMap::new();
-    let mut my_ticket: Vec<usize> = vec![];
-    let mut nearby_tickets: Vec<Vec<usize>> = vec![];
-
-    for (i, input_split) in input.split("\n\n").enumerate() {
-        match i {
-            0 => {
-                // Ticket rules
-                use regex::Regex;
-                lazy_static! {
-                    static ref DAY_16_TICKET_RULES_REGEX: Regex =
-                        Regex::new(r"^(?P<rule>[a-z ]+): (?P<number_1>\d+)-(?P<number_2>\d+) or (?P<number_3>\d+)-(?P<number_4>\d+)$")
-                            .expect("Invalid DAY_16_TICKET_RULES_REGEX!");
-                }
-                for line in input_split.lines() {
-                    if let Some(cap) = DAY_16_TICKET_RULES_REGEX.captures(line) {
-                        match ticket_rules.insert(
-                            cap["rule"].to_string(),
-                            (
-                                (
-                                    cap["number_1"].parse::<usize>()?,
-                                    cap["number_2"].parse::<usize>()?,
-                                ),
-                                (
-                                    cap["number_3"].parse::<usize>()?,
-                                    cap["number_4"].parse::<usize>()?,
-                                ),
-                            ),
-                        ) {
-                            Some(_) => err!("Ticket rule is defined twice : {}", &cap["rule"]),
-                            None => {}
-                        }
-                    } else {
-                        err!("Couldn't parse input : {}", line)
-                    }
+// Static interval tree : intervals are sorted by start and seen as an implicit balanced binary
+// search tree, every middle element storing the maximum end of its subtree
+struct IntervalTree {
+    // Interval along with the index of the rule it belongs to
+    intervals: Vec<(RangeInclusive<u64>, usize)>,
+    max_ends: Vec<u64>,
+}
+
+impl IntervalTree {
+    fn new(rules: &[Rule]) -> Self {
+        let mut intervals: Vec<(RangeInclusive<u64>, usize)> = rules
+            .iter()
+            .enumerate()
+            .flat_map(|(i, rule)| rule.ranges.iter().map(move |range| (range.clone(), i)))
+            .collect();
+        intervals.sort_by_key(|(range, _)| *range.start());
+
+        let mut tree = IntervalTree {
+            max_ends: vec![0; intervals.len()],
+            intervals,
+        };
+        tree.build(0, tree.intervals.len());
+        tree
+    }
+
+    fn build(&mut self, low: usize, high: usize) -> u64 {
+        if low >= high {
+            return 0;
+        }
+        let middle = (low + high) / 2;
+        let max_end = self
+            .build(low, middle)
+            .max(self.build(middle + 1, high))
+            .max(*self.intervals[middle].0.end());
+        self.max_ends[middle] = max_end;
+        max_end
+    }
+
+    // Rules having at least one range containing the value, possibly repeated
+    fn rules_matching(&self, value: u64) -> Vec<usize> {
+        let mut rules = vec![];
+        self.search(value, 0, self.intervals.len(), &mut rules);
+        rules
+    }
+
+    fn search(&self, value: u64, low: usize, high: usize, rules: &mut Vec<usize>) {
+        if low >= high {
+            return;
+        }
+        let middle = (low + high) / 2;
+        if self.max_ends[middle] < value {
+            return;
+        }
+
+        self.search(value, low, middle, rules);
+        let (range, rule) = &self.intervals[middle];
+        // Intervals on the right start after this one, they can't contain smaller values
+        if *range.start() <= value {
+            if range.contains(&value) {
+                rules.push(*rule);
+            }
+            self.search(value, middle + 1, high, rules);
+        }
+    }
+
+    fn is_valid(&self, value: u64) -> bool {
+        !self.rules_matching(value).is_empty()
+    }
+}
+
+// Bipartite graph between fields (left) and columns (right)
+#[derive(Clone)]
+struct BipartiteGraph {
+    edges: Vec<Vec<usize>>,
+    right_count: usize,
+}
+
+impl BipartiteGraph {
+    // Hopcroft-Karp : shortest augmenting paths are found by layers with a breadth first search,
+    // then vertex-disjoint ones are applied with a depth first search, until none is left
+    fn maximum_matching(&self) -> Vec<Option<usize>> {
+        let mut match_left: Vec<Option<usize>> = vec![None; self.edges.len()];
+        let mut match_right: Vec<Option<usize>> = vec![None; self.right_count];
+        let mut layers = vec![usize::MAX; self.edges.len()];
+
+        loop {
+            let mut queue = VecDeque::new();
+            for (left, matched) in match_left.iter().enumerate() {
+                if matched.is_none() {
+                    layers[left] = 0;
+                    queue.push_back(left);
+                } else {
+                    layers[left] = usize::MAX;
                 }
             }
-            1 => {
-                // My ticket
-                for (j, line) in input_split.lines().enumerate() {
-                    match j {
-                        0 => {}
-                        1 => {
-                            for number in line.split(',') {
-                                my_ticket.push(number.parse::<usize>()?);
-                            }
+
+            let mut augmenting_path = false;
+            while let Some(left) = queue.pop_front() {
+                for &right in &self.edges[left] {
+                    match match_right[right] {
+                        None => augmenting_path = true,
+                        Some(next) if layers[next] == usize::MAX => {
+                            layers[next] = layers[left] + 1;
+                            queue.push_back(next);
                         }
-                        _ => err!("Input invalid : my ticket have too many lines"),
+                        Some(_) => {}
                     }
                 }
             }
-            2 => {
-                for (j, line) in input_split.lines().enumerate() {
-                    match j {
-                        0 => {}
-                        _ => {
-                            let mut current_nearby_ticket = vec![];
-                            for number in line.split(',') {
-                                current_nearby_ticket.push(number.parse::<usize>()?);
-                            }
-                            nearby_tickets.push(current_nearby_ticket);
-                        }
-                    }
+            if !augmenting_path {
+                return match_left;
+            }
+
+            for left in 0..self.edges.len() {
+                if match_left[left].is_none() {
+                    self.augment(left, &mut layers, &mut match_left, &mut match_right);
                 }
             }
-            _ => err!("Invalid input"),
         }
     }
 
-    Ok((ticket_rules, my_ticket, nearby_tickets))
-}
-
-fn part_1(
-    ticket_rules: &HashMap<String, ((usize, usize), (usize, usize))>,
-    nearby_tickets: &[Vec<usize>]
-) -> usize {
-    let mut result = 0;
-
-    for current_nearby_ticket in nearby_tickets {
-        'inner: for &number in current_nearby_ticket {
-            for rule in ticket_rules.values() {
-                if (number >= rule.0.0 && number <= rule.0.1)
-                    || (number >= rule.1.0 && number <= rule.1.1)
-                {
-                    continue 'inner;
+    fn augment(
+        &self,
+        left: usize,
+        layers: &mut [usize],
+        match_left: &mut [Option<usize>],
+        match_right: &mut [Option<usize>],
+    ) -> bool {
+        for &right in &self.edges[left] {
+            let augmented = match match_right[right] {
+                None => true,
+                Some(next) => {
+                    layers[next] == layers[left] + 1
+                        && self.augment(next, layers, match_left, match_right)
                 }
+            };
+            if augmented {
+                match_left[left] = Some(right);
+                match_right[right] = Some(left);
+                return true;
             }
-            result += number;
         }
+        // Dead end, no need to explore it again during this phase
+        layers[left] = usize::MAX;
+        false
     }
 
-    result
+    fn matching_size(&self) -> usize {
+        self.maximum_matching().iter().flatten().count()
+    }
 }
 
-fn part_2(ticket_rules: &HashMap<String, ((usize, usize), (usize, usize))>, my_ticket: &[usize], nearby_tickets: &[Vec<usize>]) -> usize {
-    let mut valid_nearby_tickets: Vec<Vec<usize>> = vec![];
+struct Notes {
+    rules: Vec<Rule>,
+    tree: IntervalTree,
+    my_ticket: Vec<u64>,
+    nearby_tickets: Vec<Vec<u64>>,
+}
 
-    let mut possible_positions: HashMap<String, Vec<usize>> = ticket_rules
-        .iter()
-        .map(|(rule_name, _)| (rule_name.to_string(), (0..my_ticket.len()).collect()))
-        .collect();
+impl Notes {
+    fn is_valid(&self, ticket: &[u64]) -> bool {
+        ticket.iter().all(|&value| self.tree.is_valid(value))
+    }
 
+    fn error_rate(&self) -> u64 {
+        self.nearby_tickets
+            .iter()
+            .flatten()
+            .filter(|&&value| !self.tree.is_valid(value))
+            .sum()
+    }
 
-    // Filter invalid nearby tickets
-    'outer: for current_nearby_ticket in nearby_tickets {
-        valid_nearby_tickets.push(current_nearby_ticket.clone());
-        'inner: for &number in current_nearby_ticket {
-            for rule in ticket_rules.values() {
-                if (number >= rule.0.0 && number <= rule.0.1)
-                    || (number >= rule.1.0 && number <= rule.1.1) {
-                    continue 'inner;
+    // Columns every field could be in, according to the valid nearby tickets
+    fn candidates(&self) -> BipartiteGraph {
+        let columns = self.my_ticket.len();
+        let mut possible = vec![vec![true; columns]; self.rules.len()];
+
+        for ticket in self.nearby_tickets.iter().filter(|t| self.is_valid(t)) {
+            for (column, &value) in ticket.iter().enumerate() {
+                let matching: HashSet<usize> =
+                    self.tree.rules_matching(value).into_iter().collect();
+                for (rule, possible) in possible.iter_mut().enumerate() {
+                    if !matching.contains(&rule) {
+                        possible[column] = false;
+                    }
                 }
             }
-            valid_nearby_tickets.pop();
-            continue 'outer;
         }
-    }
 
-    for (rule_name, rule) in ticket_rules {
-        let mut current_invalid_positions = vec![];
-        for current_valid_nearby_ticket in &valid_nearby_tickets {
-            let mut x: Vec<usize> = current_valid_nearby_ticket
+        BipartiteGraph {
+            edges: possible
                 .iter()
-                .enumerate()
-                .filter(|(_, &v)| !((v >= rule.0.0 && v <= rule.0.1) || (v >= rule.1.0 && v <= rule.1.1)))
-                .map(|(i, _)| i)
-                .collect();
-            current_invalid_positions.append(&mut x);
+                .map(|possible| (0..columns).filter(|&column| possible[column]).collect())
+                .collect(),
+            right_count: columns,
         }
-        current_invalid_positions.sort_unstable();
-        current_invalid_positions.dedup();
-        if let Some(p) = possible_positions.get_mut(rule_name) {
-            *p = p
-                .iter()
-                .filter(|v| !current_invalid_positions.contains(v))
-                .copied()
-                .collect();
-        } else {
-            unreachable!();
+    }
+
+    // Column of every field, which must be the only one possible
+    fn assignment(&self) -> std::result::Result<Vec<usize>, AssignmentError> {
+        let graph = self.candidates();
+        let matching = graph.maximum_matching();
+        let fields = |rules: Vec<usize>| -> Vec<String> {
+            rules
+                .into_iter()
+                .map(|rule| self.rules[rule].field.clone())
+                .collect()
+        };
+
+        let unmatched: Vec<usize> = (0..self.rules.len())
+            .filter(|&rule| matching[rule].is_none())
+            .collect();
+        if !unmatched.is_empty() {
+            return Err(AssignmentError::Impossible(fields(unmatched)));
         }
+
+        // An assignment is forced when forbidding it leaves no complete matching
+        let columns: Vec<usize> = matching.into_iter().flatten().collect();
+        let ambiguous: Vec<usize> = (0..self.rules.len())
+            .filter(|&rule| {
+                let mut graph = graph.clone();
+                graph.edges[rule].retain(|&column| column != columns[rule]);
+                graph.matching_size() == self.rules.len()
+            })
+            .collect();
+        if !ambiguous.is_empty() {
+            return Err(AssignmentError::Ambiguous(fields(ambiguous)));
+        }
+
+        Ok(columns)
     }
+}
 
-    let mut result = 1;
+impl TryFrom<&str> for Notes {
+    type Error = Box<dyn Error>;
 
-    let mut positions_found = vec![];
-    let mut last_position_found = 0;
+    fn try_from(input: &str) -> Result<Self> {
+        use regex::Regex;
+        lazy_static! {
+            static ref DAY_16_TICKET_RULES_REGEX: Regex =
+                Regex::new(r"^(?P<field>[a-z ]+): (?P<ranges>\d+-\d+( or \d+-\d+)*)$")
+                    .expect("Invalid DAY_16_TICKET_RULES_REGEX!");
+        }
 
-    loop {
-        let mut new_position_found = false;
-        for (rule_name, positions) in &possible_positions {
-            if positions.len() == 1 {
-                last_position_found = positions[0];
-                positions_found.push(last_position_found);
-                new_position_found = true;
-                if rule_name.starts_with("departure") {
-                    result *= my_ticket[last_position_found];
+        fn parse_ticket(line: &str) -> Result<Vec<u64>> {
+            let mut ticket = vec![];
+            for number in line.split(',') {
+                match number.trim().parse::<u64>() {
+                    Ok(number) => ticket.push(number),
+                    Err(_) => err!("Invalid ticket number : {}", line),
                 }
             }
+            Ok(ticket)
         }
 
-        if new_position_found {
-            for (_, positions) in possible_positions.iter_mut() {
-                *positions = positions
-                    .iter()
-                    .filter(|&v| *v != last_position_found)
-                    .copied()
-                    .collect()
+        let mut sections = vec![vec![]];
+        for line in input.lines().map(str::trim_end) {
+            if line.is_empty() {
+                sections.push(vec![]);
+            } else if let Some(section) = sections.last_mut() {
+                section.push(line);
             }
-        } else {
-            break
         }
+        sections.retain(|section| !section.is_empty());
+
+        let (rule_lines, my_ticket_lines, nearby_lines) = match sections.as_slice() {
+            [rules, my_ticket, nearby] => (rules, my_ticket, nearby),
+            _ => err!(
+                "Expected rules, my ticket and nearby tickets, found {} sections",
+                sections.len()
+            ),
+        };
+
+        let mut rules: Vec<Rule> = vec![];
+        for line in rule_lines {
+            let cap = match DAY_16_TICKET_RULES_REGEX.captures(line) {
+                Some(cap) => cap,
+                None => err!("Couldn't parse input : {}", line),
+            };
+            if rules.iter().any(|rule| rule.field == cap["field"]) {
+                err!("Ticket rule is defined twice : {}", &cap["field"])
+            }
+
+            let mut ranges = vec![];
+            for range in cap["ranges"].split(" or ") {
+                if let Some((start, end)) = range.split_once('-') {
+                    ranges.push(start.parse::<u64>()?..=end.parse::<u64>()?);
+                }
+            }
+            rules.push(Rule {
+                field: cap["field"].to_string(),
+                ranges,
+            });
+        }
+
+        let my_ticket = match my_ticket_lines.as_slice() {
+            ["your ticket:", ticket] => parse_ticket(ticket)?,
+            _ => err!("Invalid input : my ticket should be a single line after `your ticket:`"),
+        };
+        let nearby_tickets = match nearby_lines.split_first() {
+            Some((&"nearby tickets:", tickets)) => tickets
+                .iter()
+                .map(|ticket| parse_ticket(ticket))
+                .collect::<Result<Vec<Vec<u64>>>>()?,
+            _ => err!("Invalid input : nearby tickets should come after `nearby tickets:`"),
+        };
+        if let Some(ticket) = nearby_tickets
+            .iter()
+            .find(|ticket| ticket.len() != my_ticket.len())
+        {
+            err!(
+                "Tickets should have {} fields, found {} : {:?}",
+                my_ticket.len(),
+                ticket.len(),
+                ticket
+            )
+        }
+
+        Ok(Notes {
+            tree: IntervalTree::new(&rules),
+            rules,
+            my_ticket,
+            nearby_tickets,
+        })
     }
+}
+
+fn main() -> Result<()> {
+    let options = Options::from_env(&[Flag::Value("--prefix")])?;
+    let prefix = options.value("--prefix").unwrap_or("departure");
 
-    result
+    day(prefix).run_with(&options)
 }
 
+fn day(prefix: &str) -> Day<Notes> {
+    let prefix = prefix.to_string();
+    Day::new(parse_input)
+        .part(part_1)
+        .part(move |notes| part_2(notes, &prefix))
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
+fn parse_input(input: &str) -> Result<Notes> {
+    Notes::try_from(input)
+}
 
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+fn part_1(notes: &Notes) -> Result<u64> {
+    Ok(notes.error_rate())
+}
 
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let (ticket_rules, _, nearby_tickets) = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(&ticket_rules, &nearby_tickets), 71);
-        Ok(())
+// Product of my ticket values for every field starting with the prefix
+fn part_2(notes: &Notes, prefix: &str) -> Result<u64> {
+    let columns = notes.assignment()?;
+
+    let values: Vec<u64> = notes
+        .rules
+        .iter()
+        .zip(columns)
+        .filter(|(rule, _)| rule.field.starts_with(prefix))
+        .map(|(_, column)| notes.my_ticket[column])
+        .collect();
+    if values.is_empty() {
+        err!("No field starts with `{}`", prefix)
     }
+    Ok(values.iter().product())
 }
 
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
+#[cfg(test)]
+aoc_runner::examples!(day("departure"), example_1);
 
+#[cfg(test)]
+mod tests {
     use super::*;
-    use std::fs::File;
-    use test::Bencher;
 
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
+    const EXAMPLE_2: &str = "class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19\n\n\
+                             your ticket:\n11,12,13\n\n\
+                             nearby tickets:\n3,9,18\n15,1,5\n5,14,9";
 
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
+    #[test]
+    fn test_assignment() -> Result<()> {
+        let notes = parse_input(EXAMPLE_2)?;
+        assert_eq!(notes.assignment()?, vec![1, 0, 2]);
+        assert_eq!(part_2(&notes, "s")?, 13);
+        assert_eq!(part_2(&notes, "")?, 11 * 12 * 13);
+        assert!(part_2(&notes, "departure").is_err());
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let (ticket_rules, _, nearby_tickets) = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(&ticket_rules, &nearby_tickets)));
+    #[test]
+    fn test_interval_tree() -> Result<()> {
+        let notes = parse_input(
+            "a: 1-3 or 10-20 or 30-30\nb: 2-12\nc: 25-40 or 50-60\n\n\
+             your ticket:\n1\n\nnearby tickets:\n1",
+        )?;
+        let matching = |value| {
+            let mut rules = notes.tree.rules_matching(value);
+            rules.sort_unstable();
+            rules
+        };
+        assert_eq!(matching(0), vec![]);
+        assert_eq!(matching(2), vec![0, 1]);
+        assert_eq!(matching(11), vec![0, 1]);
+        assert_eq!(matching(22), vec![]);
+        assert_eq!(matching(30), vec![0, 2]);
+        assert_eq!(matching(60), vec![2]);
+        assert_eq!(matching(61), vec![]);
         Ok(())
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let (ticket_rules, my_ticket, nearby_tickets) = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(&ticket_rules, &my_ticket, &nearby_tickets)));
+    #[test]
+    fn test_invalid_assignments() -> Result<()> {
+        // Both fields accept both columns
+        let notes = parse_input("a: 0-9\nb: 0-9\n\nyour ticket:\n1,2\n\nnearby tickets:\n3,4")?;
+        assert_eq!(
+            notes.assignment(),
+            Err(AssignmentError::Ambiguous(vec![
+                "a".to_string(),
+                "b".to_string()
+            ]))
+        );
+
+        // Both fields only accept the first column
+        let notes = parse_input(
+            "a: 0-9 or 20-29\nb: 0-9 or 30-39\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,25\n1,35",
+        )?;
+        assert!(matches!(
+            notes.assignment(),
+            Err(AssignmentError::Impossible(_))
+        ));
         Ok(())
     }
 }
@@ -1,23 +1,10 @@
-#![cfg_attr(feature = "unstable", feature(test))]
-
 // Launch program : cargo run --release < input/input.txt
-// Launch benchmark : cargo +nightly bench --features "unstable"
-
-/*
-Benchmark results:
-
-    running 5 tests
-    test tests::test_part_1 ... ignored
-    test tests::test_part_2 ... ignored
-    test bench::bench_parse_input ... bench:       4,944 ns/iter (+/- 469)
-    test bench::bench_part_1      ... bench:  67,192,333 ns/iter (+/- 2,347,512)
-    test bench::bench_part_2      ... bench: 908,377,293 ns/iter (+/- 156,213,295)
-
-*/
+// Launch benchmark : cargo run --release -- --bench < input/input.txt
+// Verify answers : cargo run --release -- --verify < input/input.txt
 
+use aoc_runner::Day;
 use std::collections::HashMap;
 use std::error::Error;
-use std::io::{self, Read, Write};
 
 type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
 
@@ -109,14 +96,6 @@ static DIRECTIONS: [(isize, isize, isize, isize); 80] = [
     (0, 0, 0, 1),
 ];
 
-#[derive(Clone)]
-struct Cube {
-    x: isize,
-    y: isize,
-    z: isize,
-    active: bool,
-}
-
 #[derive(Clone)]
 struct Map {
     cubes: HashMap<(isize, isize, isize, isize), bool>,
@@ -215,14 +194,13 @@ impl Map {
 }
 
 fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    let map = parse_input(&input)?;
+    day().run()
+}
 
-    writeln!(io::stdout(), "Part 1 : {}", part_1(map.clone()))?;
-    writeln!(io::stdout(), "Part 2 : {}", part_2(map))?;
-    Ok(())
+fn day() -> Day<Map> {
+    Day::new(parse_input)
+        .part(|map| Ok(part_1(map.clone())))
+        .part(|map| Ok(part_2(map.clone())))
 }
 
 fn parse_input(input: &str) -> Result<Map> {
@@ -268,63 +246,4 @@ fn part_2(mut map: Map) -> usize {
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-
-    fn read_test_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/test.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[test]
-    fn test_part_1() -> Result<()> {
-        let map = parse_input(&read_test_file()?)?;
-        assert_eq!(part_1(map), 112);
-        Ok(())
-    }
-
-    #[test]
-    fn test_part_2() -> Result<()> {
-        let map = parse_input(&read_test_file()?)?;
-        assert_eq!(part_2(map), 848);
-        Ok(())
-    }
-}
-
-#[cfg(all(feature = "unstable", test))]
-mod bench {
-    extern crate test;
-
-    use super::*;
-    use std::fs::File;
-    use test::Bencher;
-
-    fn read_input_file() -> Result<String> {
-        let mut input = String::new();
-        File::open("input/input.txt")?.read_to_string(&mut input)?;
-        Ok(input)
-    }
-
-    #[bench]
-    fn bench_parse_input(b: &mut Bencher) -> Result<()> {
-        let input = read_input_file()?;
-        b.iter(|| test::black_box(parse_input(&input)));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_1(processor.clone())));
-        Ok(())
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) -> Result<()> {
-        let processor = parse_input(&read_input_file()?)?;
-        b.iter(|| test::black_box(part_2(processor.clone())));
-        Ok(())
-    }
-}
+aoc_runner::examples!(day(), example_1);
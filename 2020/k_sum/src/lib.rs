@@ -0,0 +1,198 @@
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::ops::Range;
+
+// Indexes of k different entries summing to the target, in increasing order of their values.
+// Entries are sorted once, then the k - 2 smallest are fixed and the last two found with two
+// pointers, so k >= 2 takes O(n^(k - 1)) time
+pub fn k_sum(values: &[i64], k: usize, target: i64) -> Option<Vec<usize>> {
+    let mut sorted: Vec<usize> = (0..values.len()).collect();
+    sorted.sort_by_key(|&i| values[i]);
+    let sorted: Vec<(i64, usize)> = sorted.into_iter().map(|i| (values[i], i)).collect();
+
+    let mut indexes = Vec::with_capacity(k);
+    if sorted_k_sum(&sorted, k, target as i128, &mut indexes) {
+        Some(indexes)
+    } else {
+        None
+    }
+}
+
+fn sorted_k_sum(sorted: &[(i64, usize)], k: usize, target: i128, indexes: &mut Vec<usize>) -> bool {
+    match k {
+        0 => target == 0,
+        1 => match sorted.binary_search_by_key(&target, |&(value, _)| value as i128) {
+            Ok(position) => {
+                indexes.push(sorted[position].1);
+                true
+            }
+            Err(_) => false,
+        },
+        2 => {
+            if sorted.len() < 2 {
+                return false;
+            }
+            let (mut low, mut high) = (0, sorted.len() - 1);
+            while low < high {
+                let sum = sorted[low].0 as i128 + sorted[high].0 as i128;
+                if sum == target {
+                    indexes.push(sorted[low].1);
+                    indexes.push(sorted[high].1);
+                    return true;
+                } else if sum < target {
+                    low += 1;
+                } else {
+                    high -= 1;
+                }
+            }
+            false
+        }
+        _ => {
+            for (i, &(value, index)) in sorted.iter().enumerate() {
+                // Every other entry is at least this one, the sum can only get bigger
+                if value as i128 * k as i128 > target {
+                    break;
+                }
+                // Skip values already tried as the smallest entry
+                if i > 0 && sorted[i - 1].0 == value {
+                    continue;
+                }
+                indexes.push(index);
+                if sorted_k_sum(&sorted[i + 1..], k - 1, target - value as i128, indexes) {
+                    return true;
+                }
+                indexes.pop();
+            }
+            false
+        }
+    }
+}
+
+// Sliding window over the last `capacity` values, answering whether two different values of the
+// window sum to a target. Pushing a value updates the window in O(1)
+#[derive(Debug, Clone)]
+pub struct PairSumWindow {
+    capacity: usize,
+    values: VecDeque<i64>,
+    counts: HashMap<i64, usize>,
+}
+
+impl PairSumWindow {
+    pub fn new(capacity: usize) -> Self {
+        PairSumWindow {
+            capacity,
+            values: VecDeque::with_capacity(capacity + 1),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    // Adds a value, returning the oldest one when it leaves the window
+    pub fn push(&mut self, value: i64) -> Option<i64> {
+        self.values.push_back(value);
+        *self.counts.entry(value).or_insert(0) += 1;
+        if self.values.len() <= self.capacity {
+            return None;
+        }
+
+        let evicted = self.values.pop_front()?;
+        if let Some(count) = self.counts.get_mut(&evicted) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&evicted);
+            }
+        }
+        Some(evicted)
+    }
+
+    // O(capacity), every value of the window being looked up once
+    pub fn has_pair_summing_to(&self, target: i64) -> bool {
+        self.counts.keys().any(|&value| {
+            let complement = target as i128 - value as i128;
+            complement != value as i128
+                && i64::try_from(complement).is_ok_and(|c| self.counts.contains_key(&c))
+        })
+    }
+}
+
+// First value, along with its index, which isn't the sum of two different values among the
+// `window` ones before it
+pub fn first_invalid(values: &[i64], window: usize) -> Option<(usize, i64)> {
+    let mut pairs = PairSumWindow::new(window);
+    for (i, &value) in values.iter().enumerate() {
+        if pairs.is_full() && !pairs.has_pair_summing_to(value) {
+            return Some((i, value));
+        }
+        pairs.push(value);
+    }
+    None
+}
+
+// Contiguous range of at least `min_len` values summing to the target, values being non-negative.
+// Two pointers in O(n) : for every end, the start only moves forward while the sum is too big
+pub fn contiguous_range(values: &[i64], target: i64, min_len: usize) -> Option<Range<usize>> {
+    debug_assert!(values.iter().all(|&value| value >= 0));
+
+    let mut start = 0;
+    let mut sum: i128 = 0;
+    for end in 0..values.len() {
+        sum += values[end] as i128;
+        while sum > target as i128 && start <= end {
+            sum -= values[start] as i128;
+            start += 1;
+        }
+        if sum == target as i128 && end + 1 - start >= min_len.max(1) {
+            return Some(start..end + 1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_k_sum() {
+        let values = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(k_sum(&values, 2, 2020), Some(vec![3, 0]));
+        assert_eq!(k_sum(&values, 3, 2020), Some(vec![2, 4, 1]));
+        assert_eq!(k_sum(&values, 4, 2020), None);
+        assert_eq!(k_sum(&values, 1, 366), Some(vec![2]));
+        assert_eq!(k_sum(&values, 0, 0), Some(vec![]));
+        assert_eq!(k_sum(&[1010], 2, 2020), None);
+        assert_eq!(k_sum(&[-5, 3, 10, -2, 7], 3, 0), Some(vec![0, 3, 4]));
+        assert_eq!(k_sum(&[-5, 3, 10, -2, 7], 4, 15), Some(vec![0, 1, 4, 2]));
+    }
+
+    #[test]
+    fn test_pair_sum_window() {
+        let mut window = PairSumWindow::new(3);
+        assert_eq!(window.push(1), None);
+        assert_eq!(window.push(2), None);
+        assert!(!window.is_full());
+        assert_eq!(window.push(2), None);
+        assert!(window.has_pair_summing_to(3));
+        assert!(!window.has_pair_summing_to(4));
+        assert_eq!(window.push(5), Some(1));
+        assert!(!window.has_pair_summing_to(3));
+        assert!(window.has_pair_summing_to(7));
+    }
+
+    #[test]
+    fn test_first_invalid_and_contiguous_range() {
+        let values = [
+            35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309,
+            576,
+        ];
+        assert_eq!(first_invalid(&values, 5), Some((14, 127)));
+        assert_eq!(contiguous_range(&values, 127, 2), Some(2..6));
+        assert_eq!(contiguous_range(&[5, 1, 4], 4, 1), Some(2..3));
+        assert_eq!(contiguous_range(&[5, 1, 4], 4, 2), None);
+        assert_eq!(contiguous_range(&values, 1, 1), None);
+        assert_eq!(contiguous_range(&[0, 0, 3], 0, 2), Some(0..2));
+    }
+}
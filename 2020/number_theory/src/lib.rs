@@ -0,0 +1,217 @@
+use std::error::Error;
+use std::fmt;
+
+// x ≡ remainder (mod modulus), the remainder is always kept in [0, modulus)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Congruence {
+    pub remainder: i128,
+    pub modulus: i128,
+}
+
+impl Congruence {
+    pub fn new(remainder: i128, modulus: i128) -> Result<Self, CrtError> {
+        if modulus <= 0 {
+            return Err(CrtError::InvalidModulus(modulus));
+        }
+        Ok(Congruence {
+            remainder: remainder.rem_euclid(modulus),
+            modulus,
+        })
+    }
+}
+
+impl fmt::Display for Congruence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x ≡ {} (mod {})", self.remainder, self.modulus)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrtError {
+    InvalidModulus(i128),
+    // No integer satisfies both congruences at once
+    Inconsistent(Congruence, Congruence),
+    // The least common multiple of the moduli does not fit in an i128
+    Overflow,
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrtError::InvalidModulus(modulus) => {
+                write!(f, "Modulus should be strictly positive, got {}", modulus)
+            }
+            CrtError::Inconsistent(first, second) => {
+                write!(
+                    f,
+                    "Congruences \"{}\" and \"{}\" are inconsistent",
+                    first, second
+                )
+            }
+            CrtError::Overflow => write!(f, "Combined modulus does not fit in an i128"),
+        }
+    }
+}
+
+impl Error for CrtError {}
+
+pub fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a.abs()
+}
+
+pub fn lcm(a: i128, b: i128) -> Option<i128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).map(i128::abs)
+}
+
+// Returns (g, x, y) such that a * x + b * y = g = gcd(a, b)
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+// Inverse of a modulo m, only exists when a and m are coprime
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(m))
+}
+
+// (a * b) mod m without overflowing, even when m is close to i128::MAX
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    let (mut a, mut b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+
+    // Double and add, both operands being lower than m every addition stays below 2 * m
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod(a: i128, b: i128, m: i128) -> i128 {
+    // a + b may overflow when m is close to i128::MAX
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+// Merges two congruences whose moduli do not have to be coprime
+pub fn merge(first: Congruence, second: Congruence) -> Result<Congruence, CrtError> {
+    let g = gcd(first.modulus, second.modulus);
+    let difference = second.remainder - first.remainder;
+    if difference % g != 0 {
+        return Err(CrtError::Inconsistent(first, second));
+    }
+
+    let modulus = lcm(first.modulus, second.modulus).ok_or(CrtError::Overflow)?;
+    // first.remainder + first.modulus * k ≡ second.remainder (mod second.modulus)
+    let reduced_modulus = second.modulus / g;
+    let inverse = match mod_inverse(first.modulus / g, reduced_modulus) {
+        Some(inverse) => inverse,
+        None => return Err(CrtError::Inconsistent(first, second)),
+    };
+    let k = mul_mod(difference / g, inverse, reduced_modulus);
+    let remainder = add_mod(first.remainder, mul_mod(first.modulus, k, modulus), modulus);
+
+    Congruence::new(remainder, modulus)
+}
+
+// Generalized Chinese remainder theorem, the solution is unique modulo the lcm of all moduli
+pub fn crt(congruences: &[Congruence]) -> Result<Congruence, CrtError> {
+    congruences
+        .iter()
+        .try_fold(Congruence::new(0, 1)?, |solution, &congruence| {
+            merge(solution, congruence)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn congruence(remainder: i128, modulus: i128) -> Congruence {
+        Congruence::new(remainder, modulus).unwrap()
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(-12, 18).0, 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(i128::MAX, 2), None);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mul_mod(i128::MAX - 1, i128::MAX - 1, i128::MAX), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(
+            crt(&[congruence(2, 3), congruence(3, 5), congruence(2, 7)]),
+            Ok(congruence(23, 105))
+        );
+        // Moduli do not have to be coprime
+        assert_eq!(
+            crt(&[congruence(2, 4), congruence(4, 6)]),
+            Ok(congruence(10, 12))
+        );
+        assert_eq!(crt(&[]), Ok(congruence(0, 1)));
+    }
+
+    #[test]
+    fn test_crt_errors() {
+        assert_eq!(
+            crt(&[congruence(1, 4), congruence(2, 6)]),
+            Err(CrtError::Inconsistent(congruence(1, 4), congruence(2, 6)))
+        );
+        assert_eq!(Congruence::new(1, 0), Err(CrtError::InvalidModulus(0)));
+
+        let large_prime = 170_141_183_460_469_231_731_687_303_715_884_105_727;
+        assert_eq!(
+            crt(&[congruence(1, large_prime), congruence(1, 3)]),
+            Err(CrtError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{err, manifest_dir, Day, Result};
+use std::error::Error;
+use std::fs;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+const WARMUP_DURATION: Duration = Duration::from_millis(300);
+const MEASUREMENT_DURATION: Duration = Duration::from_secs(2);
+// Fast routines are run several times per sample so that each sample lasts at least this long
+const MIN_SAMPLE_DURATION: Duration = Duration::from_millis(1);
+const MIN_SAMPLES: usize = 5;
+const MAX_SAMPLES: usize = 100;
+// A routine whose median got slower than the baseline by more than this ratio is a regression
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
+const BASELINE_FILE: &str = "benchmark.csv";
+const CSV_HEADER: &str = "name,samples,iterations,mean_ns,median_ns,min_ns,max_ns,std_dev_ns";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub samples: usize,
+    pub iterations: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+    pub std_dev: f64,
+}
+
+impl Statistics {
+    // Every sample is the mean duration of one iteration, in nanoseconds
+    fn from_samples(mut samples: Vec<f64>, iterations: u64) -> Self {
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let count = samples.len();
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        let median = if count.is_multiple_of(2) {
+            (samples[count / 2 - 1] + samples[count / 2]) / 2.0
+        } else {
+            samples[count / 2]
+        };
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count as f64;
+
+        Statistics {
+            samples: count,
+            iterations,
+            mean,
+            median,
+            min: samples[0],
+            max: samples[count - 1],
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    pub statistics: Statistics,
+}
+
+pub fn measure<R>(mut routine: impl FnMut() -> R) -> Statistics {
+    let warmup_start = Instant::now();
+    let mut warmup_iterations = 0;
+    while warmup_iterations == 0 || warmup_start.elapsed() < WARMUP_DURATION {
+        black_box(routine());
+        warmup_iterations += 1;
+    }
+    let estimate = warmup_start.elapsed() / warmup_iterations;
+
+    let iterations = if estimate < MIN_SAMPLE_DURATION {
+        (MIN_SAMPLE_DURATION.as_nanos() / estimate.as_nanos().max(1)) as u64
+    } else {
+        1
+    };
+    let sample_duration = estimate.as_nanos().max(1) * iterations as u128;
+    let samples = ((MEASUREMENT_DURATION.as_nanos() / sample_duration) as usize)
+        .clamp(MIN_SAMPLES, MAX_SAMPLES);
+
+    let samples = (0..samples)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(routine());
+            }
+            start.elapsed().as_nanos() as f64 / iterations as f64
+        })
+        .collect();
+
+    Statistics::from_samples(samples, iterations)
+}
+
+fn measure_day<T>(day: &Day<T>, input: &str) -> Result<Vec<Measurement>> {
+    let mut measurements = vec![Measurement {
+        name: "parse_input".to_string(),
+        statistics: measure(|| day.parse_input(input)),
+    }];
+
+    let parsed_input = day.parse_input(input)?;
+    for part in 1..=day.parts() {
+        // Fail early rather than benchmarking an error path
+        day.solve(part, &parsed_input)?;
+        measurements.push(Measurement {
+            name: format!("part_{}", part),
+            statistics: measure(|| day.solve(part, &parsed_input)),
+        });
+    }
+
+    Ok(measurements)
+}
+
+pub fn to_csv(measurements: &[Measurement]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for measurement in measurements {
+        let s = &measurement.statistics;
+        csv.push_str(&format!(
+            "{},{},{},{:.0},{:.0},{:.0},{:.0},{:.0}\n",
+            measurement.name, s.samples, s.iterations, s.mean, s.median, s.min, s.max, s.std_dev
+        ));
+    }
+    csv
+}
+
+pub fn from_csv(csv: &str) -> Result<Vec<Measurement>> {
+    let mut lines = csv.lines();
+    if lines.next() != Some(CSV_HEADER) {
+        err!("Invalid benchmark file : header should be `{}`", CSV_HEADER)
+    }
+
+    let mut measurements = vec![];
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != 8 {
+            err!("Invalid benchmark line : {}", line)
+        }
+        measurements.push(Measurement {
+            name: fields[0].to_string(),
+            statistics: Statistics {
+                samples: fields[1].parse()?,
+                iterations: fields[2].parse()?,
+                mean: fields[3].parse()?,
+                median: fields[4].parse()?,
+                min: fields[5].parse()?,
+                max: fields[6].parse()?,
+                std_dev: fields[7].parse()?,
+            },
+        });
+    }
+    Ok(measurements)
+}
+
+fn format_ns(ns: f64) -> String {
+    let digits = format!("{:.0}", ns);
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+pub fn run<T>(day: &Day<T>, input: &str, save_baseline: bool) -> Result<()> {
+    let measurements = measure_day(day, input)?;
+
+    let baseline_path = manifest_dir().join(BASELINE_FILE);
+    let baseline = if baseline_path.exists() {
+        from_csv(&fs::read_to_string(&baseline_path)?)?
+    } else {
+        vec![]
+    };
+
+    let mut regressions = 0;
+    writeln!(io::stdout(), "Benchmark results:")?;
+    for measurement in &measurements {
+        let s = &measurement.statistics;
+        write!(
+            io::stdout(),
+            "    {:<12} : {:>15} ns/iter (+/- {})",
+            measurement.name,
+            format_ns(s.median),
+            format_ns(s.std_dev)
+        )?;
+
+        if let Some(previous) = baseline.iter().find(|m| m.name == measurement.name) {
+            let change = s.median / previous.statistics.median - 1.0;
+            write!(io::stdout(), "   {:+.1}% against baseline", change * 100.0)?;
+            if change > REGRESSION_THRESHOLD {
+                write!(io::stdout(), "   REGRESSION")?;
+                regressions += 1;
+            }
+        }
+        writeln!(io::stdout())?;
+    }
+
+    let results_path = if save_baseline || baseline.is_empty() {
+        baseline_path.clone()
+    } else {
+        let target_dir = manifest_dir().join("target");
+        fs::create_dir_all(&target_dir)?;
+        target_dir.join(BASELINE_FILE)
+    };
+    fs::write(&results_path, to_csv(&measurements))?;
+    writeln!(
+        io::stdout(),
+        "Results saved to \"{}\"",
+        results_path.display()
+    )?;
+
+    if regressions > 0 && !save_baseline {
+        err!(
+            "{} regression(s) found against baseline \"{}\"",
+            regressions,
+            baseline_path.display()
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let statistics = Statistics::from_samples(vec![4.0, 1.0, 3.0, 2.0], 10);
+        assert_eq!(statistics.samples, 4);
+        assert_eq!(statistics.iterations, 10);
+        assert_eq!(statistics.mean, 2.5);
+        assert_eq!(statistics.median, 2.5);
+        assert_eq!((statistics.min, statistics.max), (1.0, 4.0));
+        assert_eq!(statistics.std_dev, 1.25f64.sqrt());
+    }
+
+    #[test]
+    fn test_csv() -> Result<()> {
+        let measurements = vec![Measurement {
+            name: "part_1".to_string(),
+            statistics: Statistics::from_samples(vec![100.0, 200.0, 300.0], 1),
+        }];
+        let csv = to_csv(&measurements);
+        assert_eq!(
+            csv,
+            format!("{}\npart_1,3,1,200,200,100,300,82\n", CSV_HEADER)
+        );
+        assert_eq!(from_csv(&csv)?[0].statistics.median, 200.0);
+        assert!(from_csv("not,a,benchmark\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_ns() {
+        assert_eq!(format_ns(94.4), "94");
+        assert_eq!(format_ns(94_294.0), "94,294");
+        assert_eq!(format_ns(112_152_263.0), "112,152,263");
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{err, Result};
+use std::env;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Mode {
+    Solve,
+    Bench { save_baseline: bool },
+    Verify,
+}
+
+// Flags specific to a day, declared on top of the runner ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    // e.g. `--report`
+    Switch(&'static str),
+    // e.g. `--unit inches`
+    Value(&'static str),
+    // Flag followed by several values, e.g. `--query YOU SAN`
+    Values(&'static str, usize),
+}
+
+impl Flag {
+    fn name(&self) -> &'static str {
+        match self {
+            Flag::Switch(name) | Flag::Value(name) | Flag::Values(name, _) => name,
+        }
+    }
+
+    fn values(&self) -> usize {
+        match self {
+            Flag::Switch(_) => 0,
+            Flag::Value(_) => 1,
+            Flag::Values(_, values) => *values,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    // Puzzle inputs are read from stdin when no file is given
+    pub inputs: Vec<PathBuf>,
+    pub part: Option<usize>,
+    pub format: Format,
+    // Flags of the day found in the arguments, in order, along with their values
+    pub flags: Vec<(&'static str, Vec<String>)>,
+}
+
+impl Options {
+    // Parses the arguments of the program, the day declaring the flags it handles itself
+    pub fn from_env(flags: &[Flag]) -> Result<Self> {
+        let args: Vec<String> = env::args().skip(1).collect();
+        Options::parse(&args, flags)
+    }
+
+    pub fn parse(args: &[String], flags: &[Flag]) -> Result<Self> {
+        let mut options = Options {
+            mode: Mode::Solve,
+            inputs: vec![],
+            part: None,
+            format: Format::Text,
+            flags: vec![],
+        };
+        let mut save_baseline = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bench" => options.mode = Mode::Bench { save_baseline },
+                "--save-baseline" => save_baseline = true,
+                "--verify" => options.mode = Mode::Verify,
+                "--input" => match args.next() {
+                    Some(file) => options.inputs.push(PathBuf::from(file)),
+                    None => err!("Missing file after --input"),
+                },
+                "--part" => match args.next().map(|part| part.parse::<usize>()) {
+                    Some(Ok(part)) if part > 0 => options.part = Some(part),
+                    _ => err!("--part expects a part number (1, 2...)"),
+                },
+                "--format" => match args.next().map(String::as_str) {
+                    Some("text") => options.format = Format::Text,
+                    Some("json") => options.format = Format::Json,
+                    _ => err!("--format expects either text or json"),
+                },
+                _ => {
+                    // Arguments specific to a day are handled by the day itself, as long as it
+                    // declared them
+                    let flag = match flags.iter().find(|flag| flag.name() == arg) {
+                        Some(flag) => flag,
+                        None => err!("Unknown argument : {}", arg),
+                    };
+                    let values: Vec<String> = args.by_ref().take(flag.values()).cloned().collect();
+                    match flag {
+                        Flag::Value(name) if values.is_empty() => {
+                            err!("Missing value after {}", name)
+                        }
+                        Flag::Values(name, count) if values.len() < *count => {
+                            err!("{} expects {} values", name, count)
+                        }
+                        _ => {}
+                    }
+                    options.flags.push((flag.name(), values));
+                }
+            }
+        }
+        if let Mode::Bench { .. } = options.mode {
+            options.mode = Mode::Bench { save_baseline };
+        }
+
+        Ok(options)
+    }
+
+    pub fn has(&self, flag: &str) -> bool {
+        self.flags.iter().any(|(name, _)| *name == flag)
+    }
+
+    // Values of every occurrence of the flag
+    pub fn values<'a>(&'a self, flag: &'a str) -> impl Iterator<Item = &'a [String]> + 'a {
+        self.flags
+            .iter()
+            .filter(move |(name, _)| *name == flag)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    // Value of the last occurrence of the flag
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == flag)
+            .and_then(|(_, values)| values.first())
+            .map(String::as_str)
+    }
+
+    pub fn parse_value<T>(&self, flag: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.value(flag).map(|value| (value, value.parse::<T>())) {
+            Some((_, Ok(value))) => Ok(Some(value)),
+            Some((value, Err(e))) => err!("Invalid value after {} : {} ({})", flag, value, e),
+            None => Ok(None),
+        }
+    }
+}
+
+pub fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_options() -> Result<()> {
+        let options = Options::parse(
+            &args("--input a.txt --part 2 --input b.txt --format json --history out"),
+            &[Flag::Value("--history")],
+        )?;
+        assert_eq!(
+            options,
+            Options {
+                mode: Mode::Solve,
+                inputs: vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")],
+                part: Some(2),
+                format: Format::Json,
+                flags: vec![("--history", vec!["out".to_string()])],
+            }
+        );
+        assert_eq!(
+            Options::parse(&args("--save-baseline --bench"), &[])?.mode,
+            Mode::Bench {
+                save_baseline: true
+            }
+        );
+
+        assert!(Options::parse(&args("--input"), &[]).is_err());
+        assert!(Options::parse(&args("--part 0"), &[]).is_err());
+        assert!(Options::parse(&args("--format xml"), &[]).is_err());
+        assert!(Options::parse(&args("--inptu a.txt"), &[]).is_err());
+        assert!(Options::parse(&args("--history out"), &[Flag::Value("--dot")]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_day_flags() -> Result<()> {
+        let flags = [Flag::Switch("--report"), Flag::Value("--unit")];
+        let options = Options::parse(&args("--unit cm --report --unit in"), &flags)?;
+        assert!(options.has("--report"));
+        assert_eq!(options.value("--unit"), Some("in"));
+        assert_eq!(options.value("--report"), None);
+        assert!(!options.has("--csv"));
+
+        let options = Options::parse(&args("--unit 12"), &flags)?;
+        assert_eq!(options.parse_value::<u32>("--unit")?, Some(12));
+        assert_eq!(options.parse_value::<u32>("--csv")?, None);
+        assert!(Options::parse(&args("--unit x"), &flags)?
+            .parse_value::<u32>("--unit")
+            .is_err());
+        assert!(Options::parse(&args("--report --unit"), &flags).is_err());
+
+        let flags = [Flag::Values("--query", 2)];
+        let options = Options::parse(&args("--query A B --query C D"), &flags)?;
+        let queries: Vec<&[String]> = options.values("--query").collect();
+        assert_eq!(queries, vec![&args("A B")[..], &args("C D")[..]]);
+        assert!(Options::parse(&args("--query A"), &flags).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json("ABC"), "\"ABC\"");
+        assert_eq!(
+            escape_json("open \"part_2.png\"\n"),
+            "\"open \\\"part_2.png\\\"\\n\""
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::verify::{parse_answers, verdict, Verdict};
+use crate::{err, Day, Result};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Every example is made of an input `<name>.txt` and of its expected answers `<name>.toml`,
+// written like `input/answers.toml`. Parts without an expected answer are not checked.
+const EXAMPLES_DIRECTORY: &str = "input/examples";
+
+fn examples_directory(manifest_dir: &str) -> PathBuf {
+    Path::new(manifest_dir).join(EXAMPLES_DIRECTORY)
+}
+
+pub fn check_example<T>(day: &Day<T>, manifest_dir: &str, name: &str) -> Result<()> {
+    let directory = examples_directory(manifest_dir);
+    let input_path = directory.join(format!("{}.txt", name));
+    let answers_path = directory.join(format!("{}.toml", name));
+
+    let input = match fs::read_to_string(&input_path) {
+        Ok(input) => input,
+        Err(e) => err!("Cannot read \"{}\" : {}", input_path.display(), e),
+    };
+    let answers = match fs::read_to_string(&answers_path) {
+        Ok(answers) => parse_answers(&answers)?,
+        Err(e) => err!("Cannot read \"{}\" : {}", answers_path.display(), e),
+    };
+    if answers.is_empty() {
+        err!("Example \"{}\" has no expected answer", name)
+    }
+    if let Some(part) = answers.keys().find(|&&part| part > day.parts()) {
+        err!(
+            "Example \"{}\" expects an answer for missing part {}",
+            name,
+            part
+        )
+    }
+
+    let parsed_input = day.parse_input(&input)?;
+    let mut failures = vec![];
+    for part in 1..=day.parts() {
+        if !answers.contains_key(&part) {
+            continue;
+        }
+        let answer = day.solve(part, &parsed_input)?;
+        if let Verdict::Fail { expected } = verdict(&answers, part, &answer) {
+            failures.push(format!(
+                "part {} expected {}, got {}",
+                part, expected, answer
+            ));
+        }
+    }
+
+    if !failures.is_empty() {
+        err!("Example \"{}\" : {}", name, failures.join(", "))
+    }
+    Ok(())
+}
+
+// Makes sure that no example on disk is forgotten by the generated tests
+pub fn check_listed(manifest_dir: &str, names: &[&str]) -> Result<()> {
+    let mut on_disk = BTreeSet::new();
+    for entry in fs::read_dir(examples_directory(manifest_dir))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "txt") {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                on_disk.insert(name.to_string());
+            }
+        }
+    }
+
+    let listed: BTreeSet<String> = names.iter().map(|name| name.to_string()).collect();
+    let unlisted: Vec<&String> = on_disk.difference(&listed).collect();
+    if !unlisted.is_empty() {
+        err!("Examples not covered by any test : {:?}", unlisted)
+    }
+    Ok(())
+}
+
+// Generates one test per named example, plus a test failing when an example on disk is not listed.
+// `$day` is evaluated in the calling module, e.g. `aoc_runner::examples!(day(), example_1, example_2);`
+#[macro_export]
+macro_rules! examples {
+    ($day:expr, $($name:ident),+ $(,)?) => {
+        mod examples {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                fn $name() -> $crate::Result<()> {
+                    $crate::fixtures::check_example(&$day, env!("CARGO_MANIFEST_DIR"), stringify!($name))
+                }
+            )+
+
+            #[test]
+            fn all_examples_listed() -> $crate::Result<()> {
+                $crate::fixtures::check_listed(env!("CARGO_MANIFEST_DIR"), &[$(stringify!($name)),+])
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum_day() -> Day<Vec<i64>> {
+        Day::new(|input: &str| {
+            input
+                .lines()
+                .map(|line| line.parse().map_err(Box::<dyn Error>::from))
+                .collect()
+        })
+        .part(|numbers: &Vec<i64>| Ok(numbers.iter().sum::<i64>()))
+        .part(|numbers: &Vec<i64>| Ok(numbers.iter().product::<i64>()))
+    }
+
+    fn write_example(directory: &Path, name: &str, input: &str, answers: &str) -> Result<()> {
+        let examples = directory.join(EXAMPLES_DIRECTORY);
+        fs::create_dir_all(&examples)?;
+        fs::write(examples.join(format!("{}.txt", name)), input)?;
+        fs::write(examples.join(format!("{}.toml", name)), answers)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_example() -> Result<()> {
+        let directory =
+            std::env::temp_dir().join(format!("aoc_runner_fixtures_{}", std::process::id()));
+        let manifest_dir = directory.to_str().unwrap();
+        write_example(&directory, "both", "2\n3\n4", "part1 = 9\npart2 = 24")?;
+        write_example(&directory, "only_part_2", "5\n5", "part2 = 25")?;
+        write_example(&directory, "wrong", "1\n1", "part1 = 3")?;
+
+        let day = sum_day();
+        let results = (
+            check_example(&day, manifest_dir, "both"),
+            check_example(&day, manifest_dir, "only_part_2"),
+            check_example(&day, manifest_dir, "wrong"),
+            check_example(&day, manifest_dir, "absent"),
+            check_listed(manifest_dir, &["both", "only_part_2", "wrong"]),
+            check_listed(manifest_dir, &["both", "wrong"]),
+        );
+        fs::remove_dir_all(&directory)?;
+
+        assert!(results.0.is_ok());
+        assert!(results.1.is_ok());
+        assert_eq!(
+            results.2.unwrap_err().to_string(),
+            "Example \"wrong\" : part 1 expected 3, got 2"
+        );
+        assert!(results.3.is_err());
+        assert!(results.4.is_ok());
+        assert!(results.5.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use std::env;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub mod bench;
+pub mod cli;
+pub mod fixtures;
+pub mod verify;
+
+pub type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
+
+macro_rules! err {
+    ($($tt:tt)*) => { return Err(Box::<dyn Error>::from(format!($($tt)*))) }
+}
+
+pub(crate) use err;
+
+use cli::{escape_json, Format, Mode};
+pub use cli::{Flag, Options};
+
+pub(crate) fn manifest_dir() -> PathBuf {
+    // Set by `cargo run`, fallback on the current directory when the binary is launched directly
+    env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
+
+type ParseInput<T> = Box<dyn Fn(&str) -> Result<T>>;
+type Part<T> = Box<dyn Fn(&T) -> Result<String>>;
+
+// Everything needed to run a day : how to parse the puzzle input and how to solve each part of it
+pub struct Day<T> {
+    parse_input: ParseInput<T>,
+    parts: Vec<Part<T>>,
+}
+
+impl<T> Day<T> {
+    pub fn new(parse_input: impl Fn(&str) -> Result<T> + 'static) -> Self {
+        Day {
+            parse_input: Box::new(parse_input),
+            parts: vec![],
+        }
+    }
+
+    pub fn part<R: Display>(mut self, part: impl Fn(&T) -> Result<R> + 'static) -> Self {
+        self.parts.push(Box::new(move |input| {
+            part(input).map(|result| result.to_string())
+        }));
+        self
+    }
+
+    pub fn parse_input(&self, input: &str) -> Result<T> {
+        (self.parse_input)(input)
+    }
+
+    pub fn solve(&self, part: usize, input: &T) -> Result<String> {
+        match self.parts.get(part - 1) {
+            Some(solve) => solve(input),
+            None => err!("Part {} does not exist", part),
+        }
+    }
+
+    pub fn parts(&self) -> usize {
+        self.parts.len()
+    }
+
+    // Solves the puzzle inputs given with `--input` (stdin by default), benchmarks them when
+    // launched with `--bench` or checks them against the accepted answers when launched with `--verify`
+    pub fn run(&self) -> Result<()> {
+        self.run_with(&Options::from_env(&[])?)
+    }
+
+    // Same as `run`, for days which parsed the arguments themselves to handle their own flags
+    pub fn run_with(&self, options: &Options) -> Result<()> {
+        match options.mode {
+            Mode::Bench { save_baseline } => bench::run(self, &read_input(options)?, save_baseline),
+            Mode::Verify => verify::run(self, &read_input(options)?),
+            Mode::Solve => self.solve_inputs(options),
+        }
+    }
+
+    fn report(&self, input: &str, part: Option<usize>) -> Result<Report> {
+        let start = Instant::now();
+        let parsed_input = self.parse_input(input)?;
+        let parse_time = start.elapsed();
+
+        let parts = match part {
+            Some(part) => vec![part],
+            None => (1..=self.parts()).collect(),
+        };
+        let mut answers = vec![];
+        for part in parts {
+            let start = Instant::now();
+            let answer = self.solve(part, &parsed_input)?;
+            answers.push((part, answer, start.elapsed()));
+        }
+
+        Ok(Report {
+            parse_time,
+            answers,
+        })
+    }
+
+    fn solve_inputs(&self, options: &Options) -> Result<()> {
+        let mut reports = vec![];
+        if options.inputs.is_empty() {
+            let report = read_source(None).and_then(|input| self.report(&input, options.part));
+            reports.push(("stdin".to_string(), report));
+        }
+        for path in &options.inputs {
+            let report =
+                read_source(Some(path)).and_then(|input| self.report(&input, options.part));
+            reports.push((path.display().to_string(), report));
+        }
+
+        match options.format {
+            Format::Text => print_text(&reports)?,
+            Format::Json => print_json(&reports)?,
+        }
+
+        let failures = reports.iter().filter(|(_, report)| report.is_err()).count();
+        if reports.len() == 1 {
+            if let Some((_, Err(e))) = reports.pop() {
+                return Err(e);
+            }
+        } else if failures > 0 {
+            err!("{} input(s) out of {} failed", failures, reports.len())
+        }
+        Ok(())
+    }
+}
+
+struct Report {
+    parse_time: Duration,
+    answers: Vec<(usize, String, Duration)>,
+}
+
+fn read_source(path: Option<&Path>) -> Result<String> {
+    let mut input = String::new();
+    match path {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(content) => input = content,
+            Err(e) => err!("Cannot read \"{}\" : {}", path.display(), e),
+        },
+        None => {
+            io::stdin().read_to_string(&mut input)?;
+        }
+    }
+    Ok(input)
+}
+
+// Puzzle input given with `--input` (stdin by default), for the modes working on a single input :
+// benchmarks, verification and the modes specific to a day
+pub fn read_input(options: &Options) -> Result<String> {
+    match options.inputs.as_slice() {
+        [] => read_source(None),
+        [path] => read_source(Some(path)),
+        _ => err!("Only one input can be used at once in this mode"),
+    }
+}
+
+fn print_text(reports: &[(String, Result<Report>)]) -> Result<()> {
+    let mut stdout = io::stdout();
+    for (name, report) in reports {
+        if reports.len() > 1 {
+            writeln!(stdout, "== {}", name)?;
+        }
+        match report {
+            Ok(report) => {
+                for (part, answer, time) in &report.answers {
+                    writeln!(stdout, "Part {} : {} ({:.2?})", part, answer, time)?;
+                }
+            }
+            // A single failing input is reported by the caller like any other error
+            Err(e) if reports.len() > 1 => writeln!(stdout, "Error : {}", e)?,
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn print_json(reports: &[(String, Result<Report>)]) -> Result<()> {
+    let mut entries = vec![];
+    for (name, report) in reports {
+        let entry = match report {
+            Ok(report) => {
+                let answers: Vec<String> = report
+                    .answers
+                    .iter()
+                    .map(|(part, answer, time)| {
+                        format!(
+                            "{{\"part\": {}, \"answer\": {}, \"time_ns\": {}}}",
+                            part,
+                            escape_json(answer),
+                            time.as_nanos()
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"input\": {}, \"parse_time_ns\": {}, \"parts\": [{}]}}",
+                    escape_json(name),
+                    report.parse_time.as_nanos(),
+                    answers.join(", ")
+                )
+            }
+            Err(e) => format!(
+                "{{\"input\": {}, \"error\": {}}}",
+                escape_json(name),
+                escape_json(&e.to_string())
+            ),
+        };
+        entries.push(format!("  {}", entry));
+    }
+    writeln!(io::stdout(), "[\n{}\n]", entries.join(",\n"))?;
+    Ok(())
+}
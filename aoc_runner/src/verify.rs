@@ -0,0 +1,173 @@
+use crate::{err, manifest_dir, Day, Result};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+
+const ANSWERS_FILE: &str = "input/answers.toml";
+const MULTI_LINE_QUOTES: &str = "\"\"\"";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+// Accepted answers are stored one per line, e.g. `part1 = 618144` or `part2 = "ABCDEF"`. Multi-line
+// answers are written between triple quotes and span the following lines
+pub fn parse_answers(content: &str) -> Result<HashMap<usize, String>> {
+    let mut answers = HashMap::new();
+
+    let mut lines = content.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => err!("Invalid answer at line {} : {}", i + 1, line),
+        };
+        let part = match key.strip_prefix("part").map(str::parse::<usize>) {
+            Some(Ok(part)) if part > 0 => part,
+            _ => err!("Invalid answer key at line {} : {}", i + 1, key),
+        };
+        let value = if let Some(first_line) = value.strip_prefix(MULTI_LINE_QUOTES) {
+            multi_line_value(first_line, &mut lines, i)?
+        } else if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            value[1..value.len() - 1].to_string()
+        } else if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            value.to_string()
+        } else {
+            err!("Invalid answer value at line {} : {}", i + 1, value)
+        };
+
+        if answers.insert(part, value).is_some() {
+            err!("Answer of part {} is defined twice", part)
+        }
+    }
+
+    Ok(answers)
+}
+
+// As in TOML, a newline right after the opening quotes is not part of the value. Following lines
+// are kept as is, trailing spaces included
+fn multi_line_value<'a>(
+    first_line: &str,
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    start: usize,
+) -> Result<String> {
+    let mut value = first_line.to_string();
+    let mut skip_newline = value.is_empty();
+    loop {
+        if let Some(end) = value.find(MULTI_LINE_QUOTES) {
+            if end + MULTI_LINE_QUOTES.len() != value.len() {
+                err!(
+                    "Unexpected content after the answer starting at line {}",
+                    start + 1
+                )
+            }
+            value.truncate(end);
+            return Ok(value);
+        }
+        match lines.next() {
+            Some((_, line)) => {
+                if !skip_newline {
+                    value.push('\n');
+                }
+                skip_newline = false;
+                value.push_str(line);
+            }
+            None => err!("Unterminated answer starting at line {}", start + 1),
+        }
+    }
+}
+
+pub fn verdict(answers: &HashMap<usize, String>, part: usize, answer: &str) -> Verdict {
+    match answers.get(&part) {
+        Some(expected) if expected == answer => Verdict::Pass,
+        Some(expected) => Verdict::Fail {
+            expected: expected.clone(),
+        },
+        None => Verdict::Missing,
+    }
+}
+
+pub fn run<T>(day: &Day<T>, input: &str) -> Result<()> {
+    let answers_path = manifest_dir().join(ANSWERS_FILE);
+    let answers = if answers_path.exists() {
+        parse_answers(&fs::read_to_string(&answers_path)?)?
+    } else {
+        HashMap::new()
+    };
+
+    let parsed_input = day.parse_input(input)?;
+    let mut failures = 0;
+    for part in 1..=day.parts() {
+        let answer = day.solve(part, &parsed_input)?;
+        match verdict(&answers, part, &answer) {
+            Verdict::Pass => writeln!(io::stdout(), "Part {} : pass ({})", part, answer)?,
+            Verdict::Fail { expected } => {
+                writeln!(
+                    io::stdout(),
+                    "Part {} : fail (expected {}, got {})",
+                    part,
+                    expected,
+                    answer
+                )?;
+                failures += 1;
+            }
+            Verdict::Missing => writeln!(io::stdout(), "Part {} : missing ({})", part, answer)?,
+        }
+    }
+
+    if failures > 0 {
+        err!(
+            "{} answer(s) differ from \"{}\"",
+            failures,
+            answers_path.display()
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() -> Result<()> {
+        let answers = parse_answers("# Accepted answers\npart1 = 618144\npart2 = \"EZALPLZR\"\n")?;
+        assert_eq!(answers[&1], "618144");
+        assert_eq!(answers[&2], "EZALPLZR");
+
+        assert!(parse_answers("part1 618144").is_err());
+        assert!(parse_answers("part0 = 1").is_err());
+        assert!(parse_answers("part1 = abc").is_err());
+        assert!(parse_answers("part1 = 1\npart1 = 2").is_err());
+
+        let answers =
+            parse_answers("part1 = \"\"\"\n\n# #  \n ## \"\"\"\npart2 = \"\"\"A\nB\"\"\"")?;
+        assert_eq!(answers[&1], "\n# #  \n ## ");
+        assert_eq!(answers[&2], "A\nB");
+        assert!(parse_answers("part1 = \"\"\"\nA\n").is_err());
+        assert!(parse_answers("part1 = \"\"\"A\"\"\" B").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verdict() -> Result<()> {
+        let answers = parse_answers("part1 = 42")?;
+        assert_eq!(verdict(&answers, 1, "42"), Verdict::Pass);
+        assert_eq!(
+            verdict(&answers, 1, "43"),
+            Verdict::Fail {
+                expected: "42".to_string()
+            }
+        );
+        assert_eq!(verdict(&answers, 2, "42"), Verdict::Missing);
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+#!/usr/bin/env bash
+# Benchmark every day of every year, extra arguments (e.g. --save-baseline) are forwarded to each day
+# Usage : ./bench.sh [--save-baseline] [year...]
+
+set -u
+
+cd "$(dirname "$0")"
+
+args=()
+years=()
+for arg in "$@"; do
+    case "$arg" in
+        --*) args+=("$arg") ;;
+        *) years+=("$arg") ;;
+    esac
+done
+if [ ${#years[@]} -eq 0 ]; then
+    years=(2015 2019 2020)
+fi
+
+failures=()
+for year in "${years[@]}"; do
+    for day in "$year"/day_*/; do
+        day=${day%/}
+        [ -f "$day/input/input.txt" ] || continue
+
+        echo "== $day"
+        if ! (cd "$day" && cargo run --release -q -- --bench "${args[@]}" < input/input.txt); then
+            failures+=("$day")
+        fi
+    done
+done
+
+if [ ${#failures[@]} -gt 0 ]; then
+    echo
+    echo "Regressions or errors found in : ${failures[*]}"
+    exit 1
+fi
@@ -0,0 +1,45 @@
+#!/usr/bin/env bash
+# Check the answers of every day against its input/answers.toml and print a pass/fail/missing table
+# Usage : ./verify.sh [year...]
+
+set -u
+
+cd "$(dirname "$0")"
+
+years=("$@")
+if [ ${#years[@]} -eq 0 ]; then
+    years=(2015 2019 2020)
+fi
+
+status_of() {
+    # Extract the verdict of a part from the output of `--verify`
+    local status
+    status=$(grep "^Part $2 : " <<< "$1" | head -n 1 | cut -d' ' -f4)
+    echo "${status:--}"
+}
+
+failures=0
+printf "%-12s %-8s %-8s\n" "Day" "Part 1" "Part 2"
+for year in "${years[@]}"; do
+    for day in "$year"/day_*/; do
+        day=${day%/}
+        [ -f "$day/input/input.txt" ] || continue
+
+        output=$(cd "$day" && cargo run --release -q -- --verify < input/input.txt 2>/dev/null)
+        result=$?
+        part_1=$(status_of "$output" 1)
+        part_2=$(status_of "$output" 2)
+        if [ $result -ne 0 ]; then
+            [ "$part_1" = "-" ] && part_1="error"
+            [ "$part_2" = "-" ] && part_2="error"
+            failures=$((failures + 1))
+        fi
+        printf "%-12s %-8s %-8s\n" "$day" "$part_1" "$part_2"
+    done
+done
+
+if [ $failures -gt 0 ]; then
+    echo
+    echo "$failures day(s) failed verification"
+    exit 1
+fi